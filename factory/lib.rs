@@ -0,0 +1,320 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+// Deploys the RBAC contracts of the organizations and keeps track of them
+#[ink::contract]
+mod rbac_factory {
+    use ink_lang::codegen::EmitEvent;
+    use ink_prelude::vec::Vec;
+    use ink_storage::{
+        traits::SpreadAllocate,
+        Mapping,
+    };
+    use rbac::PolicyOp;
+
+    type OrgId = [u8; 32];
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct RBACFactory {
+        // The deployer of the factory, who can change the RBAC code hash
+        owner: AccountId,
+
+        // Code hash the new RBAC instances are instantiated from
+        rbac_code_hash: Hash,
+
+        // map_org_registrant : key - OrgId, value - AccountId which can deploy the instances of the organization besides the owner
+        map_org_registrant: Mapping<OrgId, AccountId>,
+
+        // map_org_instances : key - OrgId, value - Vec<AccountId> of the RBAC instances of the organization
+        map_org_instances: Mapping<OrgId, Vec<AccountId>>,
+
+        // map_instance_code_hash : key - AccountId of the RBAC instance, value - code hash it was instantiated from
+        map_instance_code_hash: Mapping<AccountId, Hash>,
+
+        // Number of the instances deployed, makes the salt of every instance unique
+        instance_count: u64,
+    }
+
+    #[ink(event)]
+    pub struct InstanceDeployed {
+        #[ink(topic)]
+        org_id: OrgId,
+        #[ink(topic)]
+        instance: AccountId,
+        owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct CodeHashChanged {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct RegistrantChanged {
+        #[ink(topic)]
+        org_id: OrgId,
+        registrant: Option<AccountId>,
+    }
+
+    /// Errors that can occur upon calling this contract.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Error {
+        // Returned if the caller is not the owner
+        NotOwner,
+
+        // Returned if the RBAC instance could not be instantiated
+        InstantiationFailed,
+
+        // Returned if the caller is neither the owner nor the registrant of the organization
+        NotRegistrant,
+    }
+
+    // Type alias for the contract's result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    // Gas limit of the instantiation, 0 forwards all the remaining gas
+    const INSTANTIATE_GAS_LIMIT: u64 = 0;
+
+    impl RBACFactory {
+        #[ink(constructor)]
+        pub fn new(rbac_code_hash: Hash) -> Self {
+            ink_lang::codegen::initialize_contract(|contract: &mut Self| {
+                contract.owner = Self::env().caller();
+                contract.rbac_code_hash = rbac_code_hash;
+            })
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn rbac_code_hash(&self) -> Hash {
+            self.rbac_code_hash
+        }
+
+        // Set the code hash the new instances are instantiated from.
+        // The deployed instances keep their code: ink! 3.0.0-rc9 can neither replace the code of a contract
+        // nor delegate calls to another code, so the factory cannot upgrade them.
+        // read_outdated_instances lists the instances the organizations have to migrate by themselves.
+        #[ink(message)]
+        pub fn set_rbac_code_hash(&mut self, code_hash: Hash) -> Result<()> {
+            self.ensure_owner()?;
+            self.rbac_code_hash = code_hash;
+            // The events of the rbac dependency make the plain self.env().emit_event ambiguous
+            EmitEvent::<RBACFactory>::emit_event(self.env(), CodeHashChanged { code_hash });
+            Ok(())
+        }
+
+        // Set the account which can deploy the instances of the organization, None leaves it to the owner
+        #[ink(message)]
+        pub fn set_org_registrant(&mut self, org_id: OrgId, registrant: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            match registrant {
+                Some(registrant) => self.map_org_registrant.insert(org_id, &registrant),
+                None => self.map_org_registrant.remove(org_id),
+            }
+            EmitEvent::<RBACFactory>::emit_event(self.env(), RegistrantChanged { org_id, registrant });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn read_org_registrant(&self, org_id: OrgId) -> Option<AccountId> {
+            self.map_org_registrant.get(org_id)
+        }
+
+        // Deploy a new RBAC instance for the organization, owned by owner and starting with the policy.
+        // Only the owner and the registrant of the organization can call it.
+        #[ink(message, payable)]
+        pub fn deploy(&mut self, org_id: OrgId, owner: AccountId, policy: Vec<PolicyOp>) -> Result<AccountId> {
+            self.deploy_with(org_id, owner, policy, Self::instantiate_rbac)
+        }
+
+        // Deploy with the given instantiation, the tests stub it as the off-chain environment cannot instantiate
+        fn deploy_with<F>(&mut self, org_id: OrgId, owner: AccountId, policy: Vec<PolicyOp>, instantiate: F) -> Result<AccountId>
+        where
+            F: FnOnce(&Self, AccountId, Vec<PolicyOp>, &[u8; 32]) -> Result<AccountId>,
+        {
+            let caller = self.env().caller();
+            if caller != self.owner && Some(caller) != self.read_org_registrant(org_id) {
+                return Err(Error::NotRegistrant);
+            }
+            let mut salt = [0u8; 32];
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(
+                &(org_id, self.instance_count),
+                &mut salt,
+            );
+            let instance = instantiate(self, owner, policy, &salt)?;
+            self.record_instance(org_id, instance, owner);
+            Ok(instance)
+        }
+
+        fn record_instance(&mut self, org_id: OrgId, instance: AccountId, owner: AccountId) {
+            self.instance_count += 1;
+            let mut instances = self.read_instances(org_id);
+            instances.push(instance);
+            self.map_org_instances.insert(org_id, &instances);
+            self.map_instance_code_hash.insert(instance, &self.rbac_code_hash);
+            EmitEvent::<RBACFactory>::emit_event(self.env(), InstanceDeployed { org_id, instance, owner });
+        }
+
+        fn instantiate_rbac(&self, owner: AccountId, policy: Vec<PolicyOp>, salt: &[u8; 32]) -> Result<AccountId> {
+            use ink_lang::ToAccountId;
+
+            rbac::RBACRef::with_owner(owner, policy)
+                .code_hash(self.rbac_code_hash)
+                .gas_limit(INSTANTIATE_GAS_LIMIT)
+                .endowment(self.env().transferred_value())
+                .salt_bytes(salt)
+                .instantiate()
+                .map(|instance| instance.to_account_id())
+                .map_err(|_| Error::InstantiationFailed)
+        }
+
+        // Read the RBAC instances of the organization
+        #[ink(message)]
+        pub fn read_instances(&self, org_id: OrgId) -> Vec<AccountId> {
+            self.map_org_instances.get(org_id).unwrap_or_default()
+        }
+
+        // Read the code hash the instance was instantiated from, None if the factory did not deploy it
+        #[ink(message)]
+        pub fn read_instance_code_hash(&self, instance: AccountId) -> Option<Hash> {
+            self.map_instance_code_hash.get(instance)
+        }
+
+        // Read the instances of the organization not running the current code hash
+        #[ink(message)]
+        pub fn read_outdated_instances(&self, org_id: OrgId) -> Vec<AccountId> {
+            self.read_instances(org_id)
+                .into_iter()
+                .filter(|instance| self.read_instance_code_hash(*instance) != Some(self.rbac_code_hash))
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests
+    {
+        use super::*;
+
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                ink_env::account_id::<ink_env::DefaultEnvironment>(),
+                1000000,
+                0,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        fn default_accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+            ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().unwrap()
+        }
+
+        // The off-chain environment cannot instantiate contracts, so the test stops at the instantiation
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract instantiation")]
+        fn deploy_instantiates_rbac() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let _ = factory.deploy([1;32], default_accounts().bob, vec![
+                PolicyOp::AddUserOrGroupToRole([1;32], [10;32]),
+            ]);
+        }
+
+        #[ink::test]
+        fn deploy_not_registrant_fail() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let accounts = default_accounts();
+            factory.set_org_registrant([1;32], Some(accounts.bob)).unwrap();
+            assert_eq!(factory.read_org_registrant([1;32]), Some(accounts.bob));
+
+            set_caller(accounts.charlie);
+            assert_eq!(factory.deploy([1;32], accounts.charlie, vec![]), Err(Error::NotRegistrant));
+            assert_eq!(factory.set_org_registrant([1;32], Some(accounts.charlie)), Err(Error::NotOwner));
+            set_caller(accounts.bob);
+            assert_eq!(factory.deploy([2;32], accounts.bob, vec![]), Err(Error::NotRegistrant));
+        }
+
+        #[ink::test]
+        fn deploy_records_instance() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let accounts = default_accounts();
+            factory.set_org_registrant([1;32], Some(accounts.bob)).unwrap();
+            let policy = vec![PolicyOp::AddUserOrGroupToRole([1;32], [10;32])];
+            let mut salts = Vec::new();
+
+            set_caller(accounts.bob);
+            let first = factory.deploy_with([1;32], accounts.charlie, policy.clone(), |factory, owner, stub_policy, salt| {
+                assert_eq!(factory.rbac_code_hash(), Hash::from([1;32]));
+                assert_eq!(owner, accounts.charlie);
+                assert_eq!(stub_policy, policy);
+                salts.push(*salt);
+                Ok(AccountId::from([11;32]))
+            }).unwrap();
+            let second = factory.deploy_with([1;32], accounts.charlie, vec![], |_, _, _, salt| {
+                salts.push(*salt);
+                Ok(AccountId::from([12;32]))
+            }).unwrap();
+
+            assert_ne!(salts[0], salts[1]);
+            assert_eq!(factory.read_instances([1;32]), vec![first, second]);
+            assert_eq!(factory.read_instance_code_hash(first), Some(Hash::from([1;32])));
+            assert!(factory.read_outdated_instances([1;32]).is_empty());
+            // RegistrantChanged and the two InstanceDeployed
+            assert_eq!(ink_env::test::recorded_events().count(), 3);
+        }
+
+        #[ink::test]
+        fn deploy_instantiation_failed_records_nothing() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let accounts = default_accounts();
+            assert_eq!(
+                factory.deploy_with([1;32], accounts.bob, vec![], |_, _, _, _| Err(Error::InstantiationFailed)),
+                Err(Error::InstantiationFailed)
+            );
+            assert!(factory.read_instances([1;32]).is_empty());
+            assert_eq!(factory.instance_count, 0);
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+        }
+
+        #[ink::test]
+        fn record_instance_works() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let accounts = default_accounts();
+            let (first, second, other) = (AccountId::from([11;32]), AccountId::from([12;32]), AccountId::from([13;32]));
+
+            factory.record_instance([1;32], first, accounts.bob);
+            factory.record_instance([1;32], second, accounts.bob);
+            factory.record_instance([2;32], other, accounts.charlie);
+            assert_eq!(factory.read_instances([1;32]), vec![first, second]);
+            assert_eq!(factory.read_instances([2;32]), vec![other]);
+            assert_eq!(factory.read_instance_code_hash(first), Some(Hash::from([1;32])));
+            assert_eq!(ink_env::test::recorded_events().count(), 3);
+        }
+
+        #[ink::test]
+        fn set_rbac_code_hash_works() {
+            let mut factory = RBACFactory::new(Hash::from([1;32]));
+            let accounts = default_accounts();
+            let (old, new) = (AccountId::from([11;32]), AccountId::from([12;32]));
+            factory.record_instance([1;32], old, accounts.bob);
+
+            factory.set_rbac_code_hash(Hash::from([2;32])).unwrap();
+            factory.record_instance([1;32], new, accounts.bob);
+            assert_eq!(factory.read_outdated_instances([1;32]), vec![old]);
+            assert_eq!(factory.read_instance_code_hash(new), Some(Hash::from([2;32])));
+
+            set_caller(accounts.bob);
+            assert_eq!(factory.set_rbac_code_hash(Hash::from([3;32])), Err(Error::NotOwner));
+        }
+    }
+}
@@ -1,9 +1,48 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+// The original storage reads build their empty Vecs with unwrap_or_else(Vec::new)
+#![allow(clippy::unwrap_or_default)]
 
 use ink_lang as ink;
 
+#[cfg(feature = "std")]
+pub mod token;
+
+// The read-only part of RBAC other contracts can call through `RBACRef`.
+// Its messages are labelled `AccessControl::<message>`, the inherent messages keep the original labels
+// and selectors for the off-chain clients.
+#[ink::trait_definition]
+pub trait AccessControl {
+    // Read User/Group Roles, including the Roles of its Groups
+    #[ink(message)]
+    fn read_user_or_group_roles(&self, user_or_group_did: [u8; 32]) -> ink_prelude::vec::Vec<[u8; 32]>;
+
+    // Read Permission for Roles
+    #[ink(message)]
+    fn read_permissions(&self, role_did: [u8; 32]) -> ink_prelude::vec::Vec<[u8; 32]>;
+
+    // Check whether the User has the Permission through any of its Roles
+    #[ink(message)]
+    fn check_access(&self, user_did: [u8; 32], permission_did: [u8; 32]) -> bool;
+}
+
+// Implemented by the contracts subscribing to the changes of a Role, Group or Permission.
+// RBAC calls it after every change of the subscribed DID, e.g. to invalidate cached decisions.
+#[ink::trait_definition]
+pub trait PolicySubscriber {
+    // Called with the changed edge and the policy version after the change
+    #[ink(message, selector = 0xf952b4d7)]
+    fn on_policy_changed(&mut self, op: self::rbac::PolicyOp, policy_version: u64);
+}
+
+pub use self::rbac::{
+    PolicyOp,
+    RBAC,
+    RBACRef,
+};
+
 #[ink::contract]
 mod rbac {
+    use super::AccessControl;
     use ink_prelude::vec::Vec;
     use ink_storage::{
         traits::{
@@ -14,6 +53,14 @@ mod rbac {
         Mapping,
     };
     use ink_prelude::collections::BTreeSet;
+    use ink_env::call::{
+        build_call,
+        utils::ReturnType,
+        ExecutionInput,
+        Selector,
+    };
+    use ink_env::CallFlags;
+    use ink_lang::selector_bytes;
 
     type DIDType = [u8; 32];
 
@@ -22,6 +69,20 @@ mod rbac {
     type UserGroupDID = DIDType;
     type RoleDID = DIDType;
     type PermissionDID = DIDType;
+    type ResourceDID = DIDType;
+    type TemplateDID = DIDType;
+    // Name of an attribute, e.g. the hash of "zone"
+    pub type AttrKey = [u8; 32];
+    type ScheduleId = u32;
+    pub type TokenHash = [u8; 32];
+    type ConstraintId = u32;
+    type SessionId = u64;
+    type RequestId = u64;
+    type ProposalId = u64;
+    type BreakGlassId = u64;
+    pub type TenantId = [u8; 32];
+    // Storage key of a DID, the DIDs of every tenant have their own key space
+    type ScopedDID = (TenantId, DIDType);
  
 
     #[derive(
@@ -69,824 +130,6090 @@ mod rbac {
         id: PermissionDID,
     }
 
-   
-    #[ink(storage)]
-    #[derive(SpreadAllocate, Default)]
-    pub struct RBAC {
-        // Note: UserDID is not the same as GroupDID. (Limitation)
 
-        // map_group_has: key - GroupDID, value- Vec<UserGroupEntity>
-        // For example:
-        // GroupDID has UserDID1, UserDID2
+    // Static separation of duty constraint:
+    // nobody may hold `cardinality` or more of the roles, directly or through the groups.
+    // The dynamic constraints have the same shape and count the roles activated in the same session.
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct SsdConstraint {
+        tenant: TenantId,
+        roles: Vec<RoleDID>,
+        cardinality: u32,
+    }
 
-        // However, currently, only the type of user can add into the group,
-        // but the type of group cannot add into the group.
-        // Therefore, all UserGroupEntities are user and we don't support the below example,
-        // GroupDID has GroupDID2
-        map_group_has: Mapping<GroupDID, Vec<UserGroupEntity>>,
+    #[derive(Clone, Copy)]
+    enum SodKind {
+        Static,
+        Dynamic,
+    }
 
-        // map_user_group_entity_belong : key - UserGroupEntity, value- Vec<GroupDID>
-        // This map is to let us find roles easily
 
-        // Example:
-        // > GroupDID has UserDID1
-        // > GroupDID3 has UserDID1
-        // UserDID1 belongs to GroupDID, GroupDID3
-        map_user_group_entity_belong: Mapping<UserGroupEntity, Vec<GroupDID>>,
+    // Request of a User for a Role, waiting for the approval of a Role manager
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct AccessRequest {
+        tenant: TenantId,
+        user_did: UserDID,
+        role_did: RoleDID,
+        // Hash of the justification kept off-chain
+        justification_hash: [u8; 32],
+        // Last block number the request can be approved at
+        expiry: BlockNumber,
+    }
 
-        // map_user_group_to_role : key - GroupDID/UserDID, value- Vec<Role>
-        map_user_group_to_role: Mapping<UserGroupDID, Vec<Role>>,
 
-        // map_role_to_permission : key - RoleDID, value- Vec<Permission>
-        map_role_to_permission: Mapping<RoleDID, Vec<Permission>>,
+    // Approvers of the changes of a sensitive Role or Permission, and how many of them have to approve a change
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct ApprovalPolicy {
+        approvers: Vec<AccountId>,
+        threshold: u32,
     }
 
-    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
-    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
-    pub enum Error 
-    {
-        // Returned if userDID is the same as GroupDID
-        UserGroupAreSame,
 
-        // Returned if given Group does not exist
-        GroupDoesNotExist,
+    // PolicyOp proposed by an admin, it is executed once every sensitive DID of it has enough approvals
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Proposal {
+        tenant: TenantId,
+        op: PolicyOp,
+        proposer: AccountId,
+        approvals: Vec<AccountId>,
+        // Last block number the proposal can be approved at
+        expiry: BlockNumber,
+    }
 
-        // Returned if given User does not exist
-        UserOrGroupDoesNotExist,
 
-        // Returned if the User is not part of the Group
-        UserOrGroupDoesNotExistInGroup,
+    // Record of a break-glass access, kept until an admin acknowledges it
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct BreakGlassUse {
+        tenant: TenantId,
+        user_did: UserDID,
+        permission_did: PermissionDID,
+        // Hash of the reason kept off-chain
+        reason_hash: [u8; 32],
+        account: AccountId,
+        block_number: BlockNumber,
+        // Last block number the access is granted at
+        expiry: BlockNumber,
+        acknowledged_by: Option<AccountId>,
+    }
 
-        // Returned if the User or the Group has been in the Group already
-        GroupHasUserOrGroupAlready,
 
-        // Returned if the User or Group has belonged in the Group already
-        UserOrGroupBelongsGroupAlready,
+    // Storage key of the DID in the tenant, so the same DID of two tenants does not collide
+    // and no DID of one tenant can address the storage of another
+    fn scoped_key(tenant: &TenantId, did: &DIDType) -> ScopedDID {
+        (*tenant, *did)
+    }
 
-        // Returned if the Group is not part of the User
-        GroupDoesNotExistInUserGroup,
 
-        // Returned if the Role does exist for the User or Group
-        RoleDoesNotExistForUserOrGroup,
+    // Session of a User, only its active roles are used by check_session_access
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Session {
+        tenant: TenantId,
+        user_did: UserDID,
+        // The account which opened the session
+        owner: AccountId,
+        active_roles: Vec<RoleDID>,
+        // Last block number the session is valid at
+        expiry: BlockNumber,
+    }
 
-        // Returned if the Role already in the User or Group
-        UserOrGroupHasRoleAlready,
 
-        // Returned if the Role does not exists
-        RoleDoesNotExist,
+    // The input of a forwarded call is appended to the selector as it is.
+    struct CallInput<'a>(&'a [u8]);
 
-        // Returned if the Permission does not exists for Role
-        PermissionNotExistInRole,
-     
-        // Returned if the Permission is already in the Role
-         RoleHasPermissionAlready,
+    impl<'a> scale::Encode for CallInput<'a> {
+        fn encode_to<T: scale::Output + ?Sized>(&self, dest: &mut T) {
+            dest.write(self.0);
+        }
     }
 
-    pub type Result<T> = core::result::Result<T, Error>;
+    // The output of a forwarded call is returned to the caller as it is.
+    struct CallOutput(Vec<u8>);
 
-    impl RBAC {
-        
-        #[ink(constructor, payable)]
-        pub fn new() -> Self 
-        {
-            ink_lang::codegen::initialize_contract(|_| {})
+    impl scale::Decode for CallOutput {
+        fn decode<I: scale::Input>(input: &mut I) -> core::result::Result<Self, scale::Error> {
+            let mut output = ink_prelude::vec![0u8; input.remaining_len()?.unwrap_or_default()];
+            input.read(&mut output)?;
+            Ok(Self(output))
         }
+    }
 
-        #[ink(constructor, payable)]
-        pub fn default() -> Self 
-        {
-            ink_lang::codegen::initialize_contract(|_| {})
-        }
 
-        fn insert_group_has(&mut self, group_did: &GroupDID, user_did: &UserDID) ->Result<()> {
-            let user_group = UserGroupEntity{ id: *user_did, is_group: false };
-            let mut vec_user_group = 
-                self.map_group_has.get(&group_did).unwrap_or_else(Vec::new);
-            if vec_user_group.contains(&user_group) {
-                return Err(Error::GroupHasUserOrGroupAlready);
-            }
-            vec_user_group.push(user_group);
-            self.map_group_has.insert(*group_did, &vec_user_group);
-            Ok(())
-        }
+    // Capability token: a cached `check_access` decision for the user and permission
+    // which can be presented off-chain until it expires or is revoked.
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct CapabilityToken {
+        // Tenant the token was issued in, verify_token checks the access in it
+        pub tenant: TenantId,
+        pub user_did: UserDID,
+        pub permission_did: PermissionDID,
+        // Last block number the token is valid at
+        pub expiry: BlockNumber,
+        pub nonce: u64,
+        pub policy_version: u64,
+    }
 
-        fn insert_user_group_belongs(&mut self, user_group_entity: UserGroupEntity, group_did: &GroupDID) -> Result<()> {
-            let mut vec_group = 
-                self.map_user_group_entity_belong.get(&user_group_entity).unwrap_or_else(Vec::new);
-            if vec_group.contains(group_did) {
-                return Err(Error::UserOrGroupBelongsGroupAlready);
-            }
-            vec_group.push(*group_did);
-            self.map_user_group_entity_belong.insert(&user_group_entity, &vec_group);
-            Ok(())
-        }
+    // The operations changing groups, roles and permissions.
+    // They can be called directly or be signed by an admin and submitted by anyone.
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum PolicyOp {
+        AddUserToGroup(UserDID, GroupDID),
+        RemoveUserFromGroup(UserDID, GroupDID),
+        AddUserOrGroupToRole(UserGroupDID, RoleDID),
+        RemoveUserOrGroupFromRole(UserGroupDID, RoleDID),
+        AddRoleToPermission(RoleDID, PermissionDID),
+        RemoveRoleFromPermission(RoleDID, PermissionDID),
+        // The Permission is given to the Role only on the resource
+        AddRoleToPermissionOn(RoleDID, PermissionDID, ResourceDID),
+        RemoveRoleFromPermissionOn(RoleDID, PermissionDID, ResourceDID),
+        // The Permissions on the parent resource apply to the child resource too
+        AddChildResource(ResourceDID, ResourceDID),
+        RemoveChildResource(ResourceDID, ResourceDID),
+    }
 
-        // Add user to group
-        #[ink(message)]
-        pub fn add_user_to_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
-            if group_did == user_did ||
-                // user_did is the same as group id
-                self.map_group_has.get(&user_did).is_some() ||
-                // group id is the same as user id
-                self.map_user_group_entity_belong.get(&UserGroupEntity {
-                    id: group_did,
-                    is_group: false
-            }).is_some() {
-                return Err(Error::UserGroupAreSame);
-            }
 
-            self.insert_group_has(&group_did, &user_did)?;
-            self.insert_user_group_belongs(UserGroupEntity{ id: user_did, is_group: false }, &group_did)?;
-            Ok(())
-        }
+    // Value a condition compares
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum Operand {
+        Const(u64),
+        // Value supplied to check_access_with_context, missing values make the comparison false
+        Context(AttrKey),
+        // Attribute of the User set by an admin, missing values make the comparison false
+        Subject(AttrKey),
+        // Milliseconds since the epoch
+        BlockTimestamp,
+        // Seconds since midnight UTC
+        SecondOfDay,
+    }
 
-        fn remove_group_has(&mut self, group_did: &GroupDID, user_did: &UserDID) -> Result<()> {
-            let mut groups = self.map_group_has
-                .get(group_did)
-                .ok_or(Error::GroupDoesNotExist)?;
-            let index = groups
-                .iter()
-                .position(|r| r.id == *user_did)
-                .ok_or(Error::UserOrGroupDoesNotExistInGroup)?;
-            groups.remove(index);
-            self.map_group_has.insert(group_did, &groups);
-            Ok(())
-        }
 
-        fn remove_user_group_belongs(&mut self, user_did: &UserDID, group_did: &GroupDID) -> Result<()> {
-            let user = UserGroupEntity{ id: *user_did, is_group: false };
-            let mut groups = self.map_user_group_entity_belong
-                .get(&user)
-                .ok_or(Error::UserOrGroupDoesNotExist)?;
-            let index = groups
-                .iter()
-                .position(|r| *r == *group_did)
-                .ok_or(Error::GroupDoesNotExistInUserGroup)?;
-            groups.remove(index);
-            self.map_user_group_entity_belong.insert(&user, &groups);
-            Ok(())
-        }
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum CmpOp {
+        Eq,
+        Ne,
+        Lt,
+        Le,
+        Gt,
+        Ge,
+    }
 
-        // Remove user from group
-        #[ink(message)]
-        pub fn remove_user_from_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
-            self.remove_group_has(&group_did, &user_did)?;
-            self.remove_user_group_belongs(&user_did, &group_did)?;
-            Ok(())
-        }
 
-        // Read User Group, return all users in group
-        // Example:
-        // GroupsDID has UserDID1, UserDID2
-        // Return UserDID1, UserDID2
-        #[ink(message)]
-        pub fn read_user_group(&self, group_did: GroupDID) -> Vec<UserDID> {
-            self.map_group_has.get(&group_did).unwrap_or_else(Vec::new)
-                .iter()
-                .map(|user| user.id)
-                .collect()
-        }
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Comparison {
+        pub left: Operand,
+        pub op: CmpOp,
+        pub right: Operand,
+    }
 
-        fn read_user_belongs(&self, user_did: UserDID) -> Vec<GroupDID> {
-            let user_entity = UserGroupEntity{ id: user_did, is_group: false };
-            self.read_user_group_entity_belongs(&user_entity)
-        }
 
-        fn read_group_belongs(&self, group_did: GroupDID) -> Vec<GroupDID> {
-            let group_entity = UserGroupEntity{ id: group_did, is_group: true };
-            self.read_user_group_entity_belongs(&group_entity)
-        }
+    // Condition of a Role-Permission link, it holds when all the comparisons of any of the groups hold.
+    // It has at most MAX_CONDITION_COMPARISONS comparisons, so evaluating it is cheap and deterministic.
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Condition {
+        pub any_of: Vec<Vec<Comparison>>,
+    }
 
-        fn read_user_group_entity_belongs(&self, user_group_entity: &UserGroupEntity) -> Vec<GroupDID> {
-            self.map_user_group_entity_belong.get(user_group_entity).unwrap_or_else(Vec::new)
+
+    // Time of the day in seconds since midnight UTC, from start until before end
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct TimeWindow {
+        pub start: u32,
+        pub end: u32,
+    }
+
+
+    // Recurring time windows, e.g. 18:00 to 22:00 on the weekdays
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Schedule {
+        // Bit 0 is Monday, bit 6 is Sunday
+        pub weekdays: u8,
+        pub windows: Vec<TimeWindow>,
+    }
+
+
+    // What a change did: the policy operation, or the selector of the other changing message
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum ChangeOp {
+        Policy(PolicyOp),
+        Message([u8; 4]),
+    }
+
+
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct ChangeRecord {
+        pub op: ChangeOp,
+        // The admin making the change, the signer of the signed operations
+        pub caller: AccountId,
+        pub block_number: BlockNumber,
+        // Policy version the change made
+        pub version: u64,
+    }
+
+
+    // The links kept in the history for check_access_at
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum EdgeKind {
+        // User to Group
+        Membership,
+        // User/Group to Role
+        Assignment,
+        // Role to Permission
+        Permission,
+    }
+
+
+    // The link to the target existed from the block on, until the block it was removed at
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct EdgeInterval {
+        pub target: DIDType,
+        pub from: BlockNumber,
+        pub until: Option<BlockNumber>,
+    }
+
+
+    // Access decision recorded by check_and_record_access
+    #[derive(scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout, Default, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct AuditEntry {
+        pub caller: AccountId,
+        pub user_did: UserDID,
+        pub permission_did: PermissionDID,
+        pub granted: bool,
+        pub block_number: BlockNumber,
+    }
+
+
+    // What a User administers without being an admin
+    #[derive(scale::Encode, scale::Decode, Clone, Copy, SpreadLayout, PackedLayout, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            Debug,
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum AdminScope {
+        // Can add Users to the Group and remove them from it
+        Group(GroupDID),
+        // Can add Users/Groups to the Role
+        Role(RoleDID),
+    }
+
+
+    impl CapabilityToken {
+        // Hash of the encoded token, which is recorded on-chain when the token is issued
+        pub fn hash(&self) -> TokenHash {
+            let mut hash = TokenHash::default();
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(self, &mut hash);
+            hash
+        }
+    }
+
+   
+    #[ink(storage)]
+    #[derive(SpreadAllocate, Default)]
+    #[allow(clippy::upper_case_acronyms)]
+    pub struct RBAC {
+        // Note: UserDID is not the same as GroupDID. (Limitation)
+
+        // map_group_has: key - GroupDID, value- Vec<UserGroupEntity>
+        // For example:
+        // GroupDID has UserDID1, UserDID2
+
+        // However, currently, only the type of user can add into the group,
+        // but the type of group cannot add into the group.
+        // Therefore, all UserGroupEntities are user and we don't support the below example,
+        // GroupDID has GroupDID2
+        map_group_has: Mapping<ScopedDID, Vec<UserGroupEntity>>,
+
+        // map_user_group_entity_belong : key - UserGroupEntity, value- Vec<GroupDID>
+        // This map is to let us find roles easily
+
+        // Example:
+        // > GroupDID has UserDID1
+        // > GroupDID3 has UserDID1
+        // UserDID1 belongs to GroupDID, GroupDID3
+        map_user_group_entity_belong: Mapping<(TenantId, UserGroupEntity), Vec<GroupDID>>,
+
+        // map_user_group_to_role : key - GroupDID/UserDID, value- Vec<Role>
+        map_user_group_to_role: Mapping<ScopedDID, Vec<Role>>,
+
+        // map_role_to_permission : key - RoleDID, value- Vec<Permission>
+        map_role_to_permission: Mapping<ScopedDID, Vec<Permission>>,
+
+        // The deployer of the contract, who can add and remove admins
+        owner: AccountId,
+
+        // map_admin : key - AccountId, value - whether the account can change the policy
+        map_admin: Mapping<AccountId, bool>,
+
+        // Tenant of the signer while execute_signed applies its operation, None otherwise
+        acting_tenant: Option<TenantId>,
+
+        // map_account_tenant : key - AccountId, value - TenantId the account acts in, the default tenant if missing
+        map_account_tenant: Mapping<AccountId, TenantId>,
+
+        // Account which can pause the contract and lock it down besides the owner
+        guardian: Option<AccountId>,
+
+        // While paused, all the mutating messages fail
+        paused: bool,
+
+        // While locked down, check_access is false except for the allowed Permissions
+        lockdown: bool,
+
+        // map_lockdown_allowed : key - (TenantId, PermissionDID), value - whether check_access works for it while locked down
+        map_lockdown_allowed: Mapping<(TenantId, PermissionDID), bool>,
+
+        // map_signer_nonce : key - AccountId, value - nonce of the next signed PolicyOp
+        map_signer_nonce: Mapping<AccountId, u64>,
+
+        // map_account_did : key - AccountId, value - UserDID the account acts as
+        map_account_did: Mapping<AccountId, UserDID>,
+
+        // map_call_permission : key - (TenantId, callee contract, selector), value - PermissionDID required to call it
+        map_call_permission: Mapping<(TenantId, AccountId, [u8; 4]), PermissionDID>,
+
+        // map_subscribers : key - GroupDID/RoleDID/PermissionDID, value - Vec<AccountId> of the subscribed contracts
+        map_subscribers: Mapping<ScopedDID, Vec<AccountId>>,
+
+        // Gas limit of every subscriber callback, so a failing subscriber cannot block the change
+        callback_gas_limit: u64,
+
+        // map_ssd_constraint : key - ConstraintId, value - SsdConstraint
+        map_ssd_constraint: Mapping<ConstraintId, SsdConstraint>,
+
+        // map_role_ssd_constraints : key - RoleDID, value - Vec<ConstraintId> of the static constraints having the Role
+        map_role_ssd_constraints: Mapping<ScopedDID, Vec<ConstraintId>>,
+
+        // Id of the next SsdConstraint
+        next_ssd_constraint_id: ConstraintId,
+
+        // map_dsd_constraint : key - ConstraintId, value - SsdConstraint of the dynamic constraint
+        map_dsd_constraint: Mapping<ConstraintId, SsdConstraint>,
+
+        // map_role_dsd_constraints : key - RoleDID, value - Vec<ConstraintId> of the dynamic constraints having the Role
+        map_role_dsd_constraints: Mapping<ScopedDID, Vec<ConstraintId>>,
+
+        // Id of the next dynamic constraint
+        next_dsd_constraint_id: ConstraintId,
+
+        // map_session : key - SessionId, value - Session
+        map_session: Mapping<SessionId, Session>,
+
+        // Id of the next Session
+        next_session_id: SessionId,
+
+        // Number of blocks a new Session lasts
+        session_lifetime: BlockNumber,
+
+        // map_role_holders : key - RoleDID, value - Vec<UserGroupDID> of the Users/Groups having the Role directly
+        map_role_holders: Mapping<ScopedDID, Vec<UserGroupDID>>,
+
+        // map_role_max_members : key - RoleDID, value - maximum number of Users having the Role, directly or through the Groups
+        map_role_max_members: Mapping<ScopedDID, u32>,
+
+        // map_group_max_members : key - GroupDID, value - maximum number of Users in the Group
+        map_group_max_members: Mapping<ScopedDID, u32>,
+
+        // map_role_manager_permission : key - RoleDID, value - PermissionDID needed to approve the requests for the Role
+        map_role_manager_permission: Mapping<ScopedDID, PermissionDID>,
+
+        // map_access_request : key - RequestId, value - pending AccessRequest
+        map_access_request: Mapping<RequestId, AccessRequest>,
+
+        // map_role_requests : key - RoleDID, value - Vec<RequestId> of the pending requests for the Role
+        map_role_requests: Mapping<ScopedDID, Vec<RequestId>>,
+
+        // Id of the next AccessRequest
+        next_request_id: RequestId,
+
+        // Number of blocks a new AccessRequest can be answered in
+        request_lifetime: BlockNumber,
+
+        // map_approval_policy : key - DID of a sensitive Role or Permission, value - ApprovalPolicy
+        map_approval_policy: Mapping<ScopedDID, ApprovalPolicy>,
+
+        // map_proposal : key - ProposalId, value - pending Proposal
+        map_proposal: Mapping<ProposalId, Proposal>,
+
+        // Id of the next Proposal
+        next_proposal_id: ProposalId,
+
+        // Number of blocks a new Proposal can be approved in
+        proposal_lifetime: BlockNumber,
+
+        // map_break_glass_duration : key - (UserDID, PermissionDID), value - number of blocks the break-glass access of the User lasts
+        map_break_glass_duration: Mapping<(ScopedDID, PermissionDID), BlockNumber>,
+
+        // map_break_glass_access : key - (UserDID, PermissionDID), value - last block number of the break-glass access
+        map_break_glass_access: Mapping<(ScopedDID, PermissionDID), BlockNumber>,
+
+        // map_break_glass_use : key - BreakGlassId, value - BreakGlassUse
+        map_break_glass_use: Mapping<BreakGlassId, BreakGlassUse>,
+
+        // Id of the next BreakGlassUse
+        next_break_glass_id: BreakGlassId,
+
+        // map_unacknowledged_break_glass : key - (TenantId, index), value - BreakGlassId not acknowledged by an admin yet
+        map_unacknowledged_break_glass: Mapping<(TenantId, u64), BreakGlassId>,
+
+        // map_unacknowledged_index : key - BreakGlassId, value - index of the BreakGlassId in map_unacknowledged_break_glass
+        map_unacknowledged_index: Mapping<BreakGlassId, u64>,
+
+        // map_unacknowledged_count : key - TenantId, value - number of the BreakGlassUses not acknowledged yet
+        map_unacknowledged_count: Mapping<TenantId, u64>,
+
+        // map_role_resource_permission : key - (RoleDID, ResourceDID), value - Vec<PermissionDID> the Role has only on the resource
+        map_role_resource_permission: Mapping<(ScopedDID, ResourceDID), Vec<PermissionDID>>,
+
+        // map_role_resources : key - RoleDID, value - Vec<ResourceDID> the Role has Permissions on
+        map_role_resources: Mapping<ScopedDID, Vec<ResourceDID>>,
+
+        // map_resource_parent : key - ResourceDID, value - parent ResourceDID
+        map_resource_parent: Mapping<ScopedDID, ResourceDID>,
+
+        // map_resource_children : key - ResourceDID, value - Vec<ResourceDID> of the child resources
+        map_resource_children: Mapping<ScopedDID, Vec<ResourceDID>>,
+
+        // map_transfer_grace : key - UserDID transferred with a grace period, value - last block it keeps its access at
+        map_transfer_grace: Mapping<ScopedDID, BlockNumber>,
+
+        // map_transfer_grace_links : key - UserDID transferred with a grace period, value - Groups and direct Roles it loses after it
+        map_transfer_grace_links: Mapping<ScopedDID, (Vec<GroupDID>, Vec<RoleDID>)>,
+
+        // map_role_template : key - TemplateDID, value - Vec<PermissionDID> the Roles made from the template get
+        map_role_template: Mapping<ScopedDID, Vec<PermissionDID>>,
+
+        // map_permission_condition : key - (RoleDID, PermissionDID), value - Condition the link holds under
+        map_permission_condition: Mapping<(ScopedDID, PermissionDID), Condition>,
+
+        // map_subject_attribute : key - (UserDID, AttrKey), value - value of the attribute of the User
+        map_subject_attribute: Mapping<(ScopedDID, AttrKey), u64>,
+
+        // map_schedule : key - (TenantId, ScheduleId), value - Schedule of the tenant
+        map_schedule: Mapping<(TenantId, ScheduleId), Schedule>,
+
+        // Id of the next Schedule
+        next_schedule_id: ScheduleId,
+
+        // map_assignment_schedule : key - (UserGroupDID, RoleDID), value - ScheduleId the User/Group has the Role in
+        map_assignment_schedule: Mapping<(ScopedDID, RoleDID), ScheduleId>,
+
+        // map_permission_schedule : key - (RoleDID, PermissionDID), value - ScheduleId the Role has the Permission in
+        map_permission_schedule: Mapping<(ScopedDID, PermissionDID), ScheduleId>,
+
+        // map_access_quota : key - (UserDID, PermissionDID), value - number of times consume_access can still succeed
+        map_access_quota: Mapping<(ScopedDID, PermissionDID), u32>,
+
+        // map_consumer : key - (TenantId, AccountId), value - whether the account can consume the access of any User of the tenant, e.g. a door controller
+        map_consumer: Mapping<(TenantId, AccountId), bool>,
+
+        // map_audit_entry : key - (TenantId, sequence number % AUDIT_LOG_CAPACITY), value - AuditEntry
+        map_audit_entry: Mapping<(TenantId, u64), AuditEntry>,
+
+        // map_audit_count : key - TenantId, value - number of the AuditEntries ever recorded in the tenant
+        map_audit_count: Mapping<TenantId, u64>,
+
+        // map_scope_admins : key - AdminScope, value - Vec<UserDID> of the Users administering it
+        map_scope_admins: Mapping<(TenantId, AdminScope), Vec<UserDID>>,
+
+        // map_user_scopes : key - UserDID, value - Vec<AdminScope> the User administers
+        map_user_scopes: Mapping<ScopedDID, Vec<AdminScope>>,
+
+        // map_policy_version : key - TenantId, value - increased by every change of the policy or the administration in the tenant,
+        // so the issued capability tokens tell which policy they were made from.
+        map_policy_version: Mapping<TenantId, u64>,
+
+        // map_edge_history : key - (EdgeKind, DID the links start from), value - Vec<EdgeInterval> of its links
+        map_edge_history: Mapping<(EdgeKind, ScopedDID), Vec<EdgeInterval>>,
+
+        // map_edge_history_since : key - (EdgeKind, DID the links start from), value - block the history of its links is complete from,
+        // set when the removed links are dropped before the retention
+        map_edge_history_since: Mapping<(EdgeKind, ScopedDID), BlockNumber>,
+
+        // Number of blocks the removed links are kept in the history for
+        history_retention: BlockNumber,
+
+        // The history is complete from this block on, it is later than the retention after the retention is extended
+        history_kept_since: BlockNumber,
+
+        // map_change : key - (TenantId, policy version % CHANGE_LOG_CAPACITY), value - ChangeRecord of the change making the version
+        map_change: Mapping<(TenantId, u64), ChangeRecord>,
+
+        // Nonce of the next issued capability token
+        token_nonce: u64,
+
+        // map_token_issued : key - (TenantId, TokenHash), value - expiry of the token issued by this contract
+        map_token_issued: Mapping<(TenantId, TokenHash), BlockNumber>,
+
+        // map_token_revoked : key - (TenantId, TokenHash), value - () if the token was revoked before its expiry
+        map_token_revoked: Mapping<(TenantId, TokenHash), ()>,
+
+        // map_issued_token : key - (TenantId, sequence number of the issuance), value - TokenHash of the issued token
+        map_issued_token: Mapping<(TenantId, u64), TokenHash>,
+
+        // map_issued_token_count : key - TenantId, value - number of the tokens of the tenant ever issued
+        map_issued_token_count: Mapping<TenantId, u64>,
+
+        // map_revoked_token : key - (TenantId, sequence number of the revocation), value - TokenHash of the revoked token
+        map_revoked_token: Mapping<(TenantId, u64), TokenHash>,
+
+        // map_revoked_token_count : key - TenantId, value - number of the tokens of the tenant ever revoked
+        map_revoked_token_count: Mapping<TenantId, u64>,
+    }
+
+    #[ink(event)]
+    pub struct TokenIssued {
+        #[ink(topic)]
+        hash: TokenHash,
+        token: CapabilityToken,
+    }
+
+    #[ink(event)]
+    pub struct TokenRevoked {
+        #[ink(topic)]
+        hash: TokenHash,
+    }
+
+    // Emitted for every subscriber callback, before the subscriber is called
+    #[ink(event)]
+    pub struct SubscriberNotified {
+        #[ink(topic)]
+        subscriber: AccountId,
+        op: PolicyOp,
+        policy_version: u64,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct LockdownChanged {
+        account: AccountId,
+        lockdown: bool,
+    }
+
+    #[ink(event)]
+    pub struct RoleRequested {
+        #[ink(topic)]
+        request_id: RequestId,
+        #[ink(topic)]
+        user_did: UserDID,
+        #[ink(topic)]
+        role_did: RoleDID,
+        justification_hash: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct RequestApproved {
+        #[ink(topic)]
+        request_id: RequestId,
+        approver: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RequestRejected {
+        #[ink(topic)]
+        request_id: RequestId,
+        rejecter: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RequestExpired {
+        #[ink(topic)]
+        request_id: RequestId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        proposer: AccountId,
+        op: PolicyOp,
+    }
+
+    #[ink(event)]
+    pub struct ProposalApproved {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        approver: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCancelled {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    // Emitted for every break-glass access, it should be alerted on
+    #[ink(event)]
+    pub struct BreakGlassUsed {
+        #[ink(topic)]
+        break_glass_id: BreakGlassId,
+        #[ink(topic)]
+        user_did: UserDID,
+        #[ink(topic)]
+        permission_did: PermissionDID,
+        reason_hash: [u8; 32],
+        expiry: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct BreakGlassAcknowledged {
+        #[ink(topic)]
+        break_glass_id: BreakGlassId,
+        admin: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccessChecked {
+        #[ink(topic)]
+        user_did: UserDID,
+        #[ink(topic)]
+        permission_did: PermissionDID,
+        caller: AccountId,
+        granted: bool,
+    }
+
+    #[ink(event)]
+    pub struct SubjectTransferred {
+        #[ink(topic)]
+        from_did: UserDID,
+        #[ink(topic)]
+        to_did: UserDID,
+        // Last block the old User keeps its access at, None if it lost it right away
+        grace_expiry: Option<BlockNumber>,
+    }
+
+    #[ink(event)]
+    pub struct AccessConsumed {
+        #[ink(topic)]
+        user_did: UserDID,
+        #[ink(topic)]
+        permission_did: PermissionDID,
+        // None if the access is not limited
+        remaining: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Error 
+    {
+        // Returned if userDID is the same as GroupDID
+        UserGroupAreSame,
+
+        // Returned if given Group does not exist
+        GroupDoesNotExist,
+
+        // Returned if given User does not exist
+        UserOrGroupDoesNotExist,
+
+        // Returned if the User is not part of the Group
+        UserOrGroupDoesNotExistInGroup,
+
+        // Returned if the User or the Group has been in the Group already
+        GroupHasUserOrGroupAlready,
+
+        // Returned if the User or Group has belonged in the Group already
+        UserOrGroupBelongsGroupAlready,
+
+        // Returned if the Group is not part of the User
+        GroupDoesNotExistInUserGroup,
+
+        // Returned if the Role does exist for the User or Group
+        RoleDoesNotExistForUserOrGroup,
+
+        // Returned if the Role already in the User or Group
+        UserOrGroupHasRoleAlready,
+
+        // Returned if the Role does not exists
+        RoleDoesNotExist,
+
+        // Returned if the Permission does not exists for Role
+        PermissionNotExistInRole,
+     
+        // Returned if the Permission is already in the Role
+         RoleHasPermissionAlready,
+
+        // Returned if the User does not have the Permission
+        AccessNotGranted,
+
+        // Returned if the token expiry is before the current block
+        TokenExpired,
+
+        // Returned if the token was not issued by this contract
+        TokenDoesNotExist,
+
+        // Returned if the token is in the revocation list already
+        TokenRevokedAlready,
+
+        // Returned if the caller is not the owner
+        NotOwner,
+
+        // Returned if the caller or the signer is not an admin
+        NotAdmin,
+
+        // Returned if the signature was not made by the signer
+        InvalidSignature,
+
+        // Returned if the nonce is not the next nonce of the signer
+        InvalidNonce,
+
+        // Returned if the deadline of the signed operation has passed
+        SignatureExpired,
+
+        // Returned if the caller is not bound to the UserDID
+        AccountNotBound,
+
+        // Returned if the selector of the callee is not allowed for the Permission
+        CallNotAllowed,
+
+        // Returned if the transferred value is not the value to forward
+        InvalidTransferredValue,
+
+        // Returned if the forwarded call failed
+        CallFailed,
+
+        // Returned if the contract has subscribed to the DID already
+        SubscriberExistsAlready,
+
+        // Returned if the contract has not subscribed to the DID
+        SubscriberDoesNotExist,
+
+        // Returned if the DID has the maximum number of subscribers already
+        TooManySubscribers,
+
+        // Returned if the callback gas limit is zero, which means no limit
+        InvalidGasLimit,
+
+        // Returned if the constraint has less than two roles or the cardinality is not between two and the number of roles
+        InvalidSsdConstraint,
+
+        // Returned if the SsdConstraint does not exist
+        SsdConstraintDoesNotExist,
+
+        // Returned if the assignment would violate the SsdConstraint with the given id
+        SsdConstraintViolated(ConstraintId),
+
+        // Returned if the activation would violate the dynamic constraint with the given id
+        DsdConstraintViolated(ConstraintId),
+
+        // Returned if the Session does not exist
+        SessionDoesNotExist,
+
+        // Returned if the caller did not open the Session
+        NotSessionOwner,
+
+        // Returned if the Session has expired
+        SessionExpired,
+
+        // Returned if the Role is active in the Session already
+        RoleActiveAlready,
+
+        // Returned if the Role is not active in the Session
+        RoleNotActive,
+
+        // Returned if the Role or the Group has the maximum number of members already
+        MemberLimitReached,
+
+        // Returned if the caller can neither administer nor manage the Role
+        NotRoleManager,
+
+        // Returned if the AccessRequest does not exist
+        RequestDoesNotExist,
+
+        // Returned if the User has a pending request for the Role already
+        RequestExistsAlready,
+
+        // Returned if the AccessRequest has expired, or has not expired yet when it is removed
+        RequestExpired,
+
+        // Returned if the threshold is zero or greater than the number of approvers
+        InvalidThreshold,
+
+        // Returned if the operation changes a sensitive Role or Permission and has to be proposed
+        ApprovalRequired,
+
+        // Returned if the proposed operation does not change any sensitive Role or Permission
+        ApprovalNotRequired,
+
+        // Returned if the Proposal does not exist
+        ProposalDoesNotExist,
+
+        // Returned if the Proposal has expired
+        ProposalExpired,
+
+        // Returned if the caller is not an approver of the Proposal
+        NotApprover,
+
+        // Returned if the caller has approved the Proposal already
+        ProposalApprovedAlready,
+
+        // Returned if the caller can not cancel the Proposal
+        NotProposer,
+
+        // Returned if the User is not allowed to break the glass for the Permission
+        BreakGlassNotAllowed,
+
+        // Returned if the BreakGlassUse does not exist
+        BreakGlassUseDoesNotExist,
+
+        // Returned if the BreakGlassUse has been acknowledged already
+        BreakGlassAcknowledgedAlready,
+
+        // Returned if the caller is neither the owner nor the guardian
+        NotGuardian,
+
+        // Returned if the contract is paused
+        ContractPaused,
+
+        // Returned if the account or the record belongs to another tenant
+        WrongTenant,
+
+        // Returned if the User administers the scope already
+        ScopeAdminExistsAlready,
+
+        // Returned if the User does not administer the scope
+        ScopeAdminDoesNotExist,
+
+        // Returned if the child resource has a parent already
+        ResourceHasParentAlready,
+
+        // Returned if the child resource is not a child of the parent
+        ResourceNotChild,
+
+        // Returned if the child resource is the parent or one of its ancestors
+        ResourceCycle,
+
+        // Returned if the child resource or one of its descendants would have more than MAX_RESOURCE_DEPTH ancestors
+        ResourceTreeTooDeep,
+
+        // Returned if the condition is empty, has an empty group or too many comparisons
+        InvalidCondition,
+
+        // Returned if the schedule has no weekday, no window, too many windows or an empty window
+        InvalidSchedule,
+
+        // Returned if the Schedule does not exist
+        ScheduleDoesNotExist,
+
+        // Returned if the caller can not consume the access of the User
+        NotConsumer,
+
+        // Returned if the quota of the User for the Permission is used up
+        QuotaExhausted,
+
+        // Returned if the records of the changes after the version are overwritten already
+        ChangesPruned,
+
+        // Returned if the history of the block is not kept
+        HistoryNotKept,
+
+        // Returned if a User/Group has cardinality or more of the roles of the new static constraint already
+        SsdConstraintViolatedAlready,
+
+        // Returned if the request can still be answered
+        RequestNotExpired,
+
+        // Returned if the new Role has Permissions already
+        RoleExistsAlready,
+
+        // Returned if the role template does not exist
+        TemplateDoesNotExist,
+
+        // Returned if the User was not transferred with a grace period
+        TransferGraceDoesNotExist,
+
+        // Returned if the grace period of the transferred User is not over yet
+        TransferGraceNotOver,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    // Selector of PolicySubscriber::on_policy_changed
+    const ON_POLICY_CHANGED_SELECTOR: [u8; 4] = [0xf9, 0x52, 0xb4, 0xd7];
+    const MAX_SUBSCRIBERS_PER_DID: usize = 16;
+    const DEFAULT_CALLBACK_GAS_LIMIT: u64 = 5_000_000_000;
+    // About an hour with 6 second blocks
+    const DEFAULT_SESSION_LIFETIME: BlockNumber = 600;
+    // Number of ancestors check_access_on looks at, and the most a new child resource can have
+    const MAX_RESOURCE_DEPTH: usize = 16;
+    const MAX_CONDITION_COMPARISONS: usize = 16;
+    const MAX_SCHEDULE_WINDOWS: usize = 8;
+    const SECONDS_PER_DAY: u64 = 86_400;
+    // The records of the oldest changes are overwritten after this many
+    const CHANGE_LOG_CAPACITY: u64 = 1024;
+    const MAX_CHANGE_PAGE: u64 = 100;
+    // The oldest removed links of a DID are dropped from the history after this many
+    const MAX_REMOVED_EDGES: usize = 64;
+    // The oldest entries of the audit log are overwritten after this many
+    const AUDIT_LOG_CAPACITY: u64 = 1024;
+    const MAX_AUDIT_PAGE: u32 = 100;
+    const MAX_REVOKED_TOKEN_PAGE: u32 = 100;
+    const MAX_ISSUED_TOKEN_PAGE: u32 = 100;
+    const MAX_BREAK_GLASS_PAGE: u32 = 100;
+    // Tenant of the accounts not assigned to any tenant
+    pub const DEFAULT_TENANT: TenantId = [0; 32];
+    // About a week with 6 second blocks
+    const DEFAULT_REQUEST_LIFETIME: BlockNumber = 100_800;
+    // About a month with 6 second blocks
+    const DEFAULT_HISTORY_RETENTION: BlockNumber = 432_000;
+    // About a day with 6 second blocks
+    const DEFAULT_PROPOSAL_LIFETIME: BlockNumber = 14_400;
+
+    impl RBAC {
+        
+        #[ink(constructor, payable)]
+        pub fn new() -> Self 
+        {
+            ink_lang::codegen::initialize_contract(Self::init)
+        }
+
+        #[ink(constructor, payable)]
+        #[allow(clippy::should_implement_trait)]
+        pub fn default() -> Self 
+        {
+            ink_lang::codegen::initialize_contract(Self::init)
+        }
+
+        // Deploy for another owner with an initial policy, e.g. from the factory contract.
+        // The deployment fails if an operation of the policy fails.
+        #[ink(constructor, payable)]
+        pub fn with_owner(owner: AccountId, policy: Vec<PolicyOp>) -> Self
+        {
+            ink_lang::codegen::initialize_contract(|contract: &mut Self| {
+                contract.init();
+                contract.owner = owner;
+                for op in policy {
+                    contract.execute_policy_op(owner, op).expect("invalid initial policy");
+                }
+            })
+        }
+
+        fn init(&mut self) {
+            self.owner = Self::env().caller();
+            self.callback_gas_limit = DEFAULT_CALLBACK_GAS_LIMIT;
+            self.session_lifetime = DEFAULT_SESSION_LIFETIME;
+            self.request_lifetime = DEFAULT_REQUEST_LIFETIME;
+            self.proposal_lifetime = DEFAULT_PROPOSAL_LIFETIME;
+            self.history_retention = DEFAULT_HISTORY_RETENTION;
+        }
+
+        fn ensure_owner(&self, account: &AccountId) -> Result<()> {
+            if *account != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        fn ensure_admin(&self, account: &AccountId) -> Result<()> {
+            if !self.is_admin(*account) {
+                return Err(Error::NotAdmin);
+            }
+            Ok(())
+        }
+
+        // Check whether the account can change the policy, the owner always can
+        #[ink(message)]
+        pub fn is_admin(&self, account: AccountId) -> bool {
+            account == self.owner || self.map_admin.get(account).unwrap_or_default()
+        }
+
+        // Add admin, only the owner can call it
+        #[ink(message)]
+        pub fn add_admin(&mut self, account: AccountId) -> Result<()> {
+            self.record_message(selector_bytes!("add_admin"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.map_admin.insert(account, &true);
+                Ok(())
+            })
+        }
+
+        // Remove admin, only the owner can call it
+        #[ink(message)]
+        pub fn remove_admin(&mut self, account: AccountId) -> Result<()> {
+            self.record_message(selector_bytes!("remove_admin"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.map_admin.remove(account);
+                Ok(())
+            })
+        }
+
+        // Read the tenant the account acts in
+        #[ink(message)]
+        pub fn read_account_tenant(&self, account: AccountId) -> TenantId {
+            self.map_account_tenant.get(account).unwrap_or(DEFAULT_TENANT)
+        }
+
+        // Move the account to the tenant, dropping its admin rights and its UserDID. Only the owner can call it.
+        #[ink(message)]
+        pub fn set_account_tenant(&mut self, account: AccountId, tenant: TenantId) -> Result<()> {
+            self.record_message(selector_bytes!("set_account_tenant"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                if tenant == DEFAULT_TENANT {
+                    rbac.map_account_tenant.remove(account);
+                } else {
+                    rbac.map_account_tenant.insert(account, &tenant);
+                }
+                rbac.map_admin.remove(account);
+                rbac.map_account_did.remove(account);
+                Ok(())
+            })
+        }
+
+        // Every message acts in the tenant of its caller, execute_signed in the tenant of the signer
+        fn caller_tenant(&self) -> TenantId {
+            self.acting_tenant.unwrap_or_else(|| self.read_account_tenant(self.env().caller()))
+        }
+
+        fn key(&self, did: &DIDType) -> ScopedDID {
+            scoped_key(&self.caller_tenant(), did)
+        }
+
+        fn entity_key(&self, entity: &UserGroupEntity) -> (TenantId, UserGroupEntity) {
+            (self.caller_tenant(), entity.clone())
+        }
+
+        fn ensure_caller_tenant(&self, tenant: &TenantId) -> Result<()> {
+            if *tenant != self.caller_tenant() {
+                return Err(Error::WrongTenant);
+            }
+            Ok(())
+        }
+
+        fn ensure_guardian(&self, account: &AccountId) -> Result<()> {
+            if *account != self.owner && Some(*account) != self.guardian {
+                return Err(Error::NotGuardian);
+            }
+            Ok(())
+        }
+
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            Ok(())
+        }
+
+        // Set the guardian, only the owner can call it
+        #[ink(message)]
+        pub fn set_guardian(&mut self, guardian: Option<AccountId>) -> Result<()> {
+            self.record_message(selector_bytes!("set_guardian"), |rbac| {
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.guardian = guardian;
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn guardian(&self) -> Option<AccountId> {
+            self.guardian
+        }
+
+        // Make all the mutating messages fail, only the owner or the guardian can call it
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.record_message(selector_bytes!("pause"), |rbac| {
+                let account = rbac.env().caller();
+                rbac.ensure_guardian(&account)?;
+                rbac.paused = true;
+                rbac.env().emit_event(Paused { account });
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.record_message(selector_bytes!("unpause"), |rbac| {
+                let account = rbac.env().caller();
+                rbac.ensure_guardian(&account)?;
+                rbac.paused = false;
+                rbac.env().emit_event(Unpaused { account });
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        // Make check_access false except for the allowed Permissions, the stored policy is kept as it is
+        #[ink(message)]
+        pub fn set_lockdown(&mut self, lockdown: bool) -> Result<()> {
+            self.record_message(selector_bytes!("set_lockdown"), |rbac| {
+                let account = rbac.env().caller();
+                rbac.ensure_guardian(&account)?;
+                rbac.lockdown = lockdown;
+                rbac.env().emit_event(LockdownChanged { account, lockdown });
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn is_lockdown(&self) -> bool {
+            self.lockdown
+        }
+
+        // Keep check_access working for the Permission of the tenant while locked down, e.g. for the emergency exits
+        #[ink(message)]
+        pub fn set_lockdown_allowed(&mut self, tenant: TenantId, permission_did: PermissionDID, allowed: bool) -> Result<()> {
+            self.record_message(selector_bytes!("set_lockdown_allowed"), |rbac| {
+                rbac.ensure_guardian(&rbac.env().caller())?;
+                if allowed {
+                    rbac.map_lockdown_allowed.insert((tenant, permission_did), &true);
+                } else {
+                    rbac.map_lockdown_allowed.remove((tenant, permission_did));
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn is_lockdown_allowed(&self, tenant: TenantId, permission_did: PermissionDID) -> bool {
+            self.map_lockdown_allowed.get((tenant, permission_did)).unwrap_or_default()
+        }
+
+        fn scope_key(&self, scope: &AdminScope) -> (TenantId, AdminScope) {
+            (self.caller_tenant(), *scope)
+        }
+
+        // Admins administer every scope, the others only the scopes given to the User they are bound to
+        fn ensure_scope_admin(&self, account: &AccountId, scope: &AdminScope) -> Result<()> {
+            if self.is_admin(*account) {
+                return Ok(());
+            }
+            let scope_admin = self.map_account_did
+                .get(account)
+                .map_or(false, |user_did| self.read_scope_admins(*scope).contains(&user_did));
+            if !scope_admin {
+                return Err(Error::NotAdmin);
+            }
+            Ok(())
+        }
+
+        // Let the User administer the scope, e.g. the membership of its team Group
+        #[ink(message)]
+        pub fn add_scope_admin(&mut self, scope: AdminScope, user_did: UserDID) -> Result<()> {
+            self.record_message(selector_bytes!("add_scope_admin"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                let mut admins = rbac.read_scope_admins(scope);
+                if admins.contains(&user_did) {
+                    return Err(Error::ScopeAdminExistsAlready);
+                }
+                admins.push(user_did);
+                rbac.map_scope_admins.insert(rbac.scope_key(&scope), &admins);
+
+                let mut scopes = rbac.read_user_scopes(user_did);
+                scopes.push(scope);
+                rbac.map_user_scopes.insert(rbac.key(&user_did), &scopes);
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn remove_scope_admin(&mut self, scope: AdminScope, user_did: UserDID) -> Result<()> {
+            self.record_message(selector_bytes!("remove_scope_admin"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                let mut admins = rbac.read_scope_admins(scope);
+                if !admins.contains(&user_did) {
+                    return Err(Error::ScopeAdminDoesNotExist);
+                }
+                admins.retain(|admin| *admin != user_did);
+                rbac.map_scope_admins.insert(rbac.scope_key(&scope), &admins);
+
+                let mut scopes = rbac.read_user_scopes(user_did);
+                scopes.retain(|s| *s != scope);
+                rbac.map_user_scopes.insert(rbac.key(&user_did), &scopes);
+                Ok(())
+            })
+        }
+
+        // Read the Users administering the scope
+        #[ink(message)]
+        pub fn read_scope_admins(&self, scope: AdminScope) -> Vec<UserDID> {
+            self.map_scope_admins.get(self.scope_key(&scope)).unwrap_or_default()
+        }
+
+        // Read the scopes the User administers
+        #[ink(message)]
+        pub fn read_user_scopes(&self, user_did: UserDID) -> Vec<AdminScope> {
+            self.map_user_scopes.get(self.key(&user_did)).unwrap_or_default()
+        }
+
+        // Changes of the sensitive Roles and Permissions can only be executed through proposals
+        fn apply_policy_op(&mut self, author: AccountId, op: PolicyOp) -> Result<()> {
+            if !self.sensitive_dids(&op).is_empty() {
+                return Err(Error::ApprovalRequired);
+            }
+            self.execute_policy_op(author, op)
+        }
+
+        fn execute_policy_op(&mut self, author: AccountId, op: PolicyOp) -> Result<()> {
+            match op.clone() {
+                PolicyOp::AddUserToGroup(user_did, group_did) =>
+                    self.apply_add_user_to_group(user_did, group_did)?,
+                PolicyOp::RemoveUserFromGroup(user_did, group_did) =>
+                    self.apply_remove_user_from_group(user_did, group_did)?,
+                PolicyOp::AddUserOrGroupToRole(user_or_group_did, role_did) =>
+                    self.apply_add_user_or_group_to_role(user_or_group_did, role_did)?,
+                PolicyOp::RemoveUserOrGroupFromRole(user_or_group_did, role_did) =>
+                    self.apply_remove_user_or_group_from_role(user_or_group_did, role_did)?,
+                PolicyOp::AddRoleToPermission(role_did, permission_did) =>
+                    self.apply_add_role_to_permission(role_did, permission_did)?,
+                PolicyOp::RemoveRoleFromPermission(role_did, permission_did) =>
+                    self.apply_remove_role_from_permission(role_did, permission_did)?,
+                PolicyOp::AddRoleToPermissionOn(role_did, permission_did, resource_did) =>
+                    self.apply_add_role_to_permission_on(role_did, permission_did, resource_did)?,
+                PolicyOp::RemoveRoleFromPermissionOn(role_did, permission_did, resource_did) =>
+                    self.apply_remove_role_from_permission_on(role_did, permission_did, resource_did)?,
+                PolicyOp::AddChildResource(parent_did, child_did) =>
+                    self.apply_add_child_resource(parent_did, child_did)?,
+                PolicyOp::RemoveChildResource(parent_did, child_did) =>
+                    self.apply_remove_child_resource(parent_did, child_did)?,
+            }
+            self.record_change(ChangeOp::Policy(op.clone()), author);
+            self.notify_subscribers(&op);
+            Ok(())
+        }
+
+        // Hash the signer has to sign for execute_signed.
+        // The contract address is included, so the signature cannot be replayed on the other RBAC contracts.
+        #[ink(message)]
+        pub fn signed_op_hash(&self, op: PolicyOp, signer: AccountId, nonce: u64, deadline: BlockNumber) -> [u8; 32] {
+            let mut hash = [0u8; 32];
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(
+                &(self.env().account_id(), op, signer, nonce, deadline),
+                &mut hash,
+            );
+            hash
+        }
+
+        // Read the nonce the next signed operation of the signer has to use
+        #[ink(message)]
+        pub fn read_signer_nonce(&self, signer: AccountId) -> u64 {
+            self.map_signer_nonce.get(signer).unwrap_or_default()
+        }
+
+        // Execute the operation signed by an admin with the ECDSA key of its account.
+        // Anyone can submit it, so the admin doesn't have to pay for the transaction.
+        #[ink(message)]
+        pub fn execute_signed(&mut self, op: PolicyOp, signer: AccountId, nonce: u64, deadline: BlockNumber, signature: [u8; 65]) -> Result<()> {
+            self.ensure_not_paused()?;
+            if deadline < self.env().block_number() {
+                return Err(Error::SignatureExpired);
+            }
+            if nonce != self.read_signer_nonce(signer) {
+                return Err(Error::InvalidNonce);
+            }
+
+            let message_hash = self.signed_op_hash(op.clone(), signer, nonce, deadline);
+            let mut public_key = [0u8; 33];
+            ink_env::ecdsa_recover(&signature, &message_hash, &mut public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            // The account of an ECDSA key is the hash of its compressed public key
+            let mut account = [0u8; 32];
+            ink_env::hash_bytes::<ink_env::hash::Blake2x256>(&public_key, &mut account);
+            if AccountId::from(account) != signer {
+                return Err(Error::InvalidSignature);
+            }
+            self.ensure_admin(&signer)?;
+
+            // The operation changes the tenant of the signer, whoever submits it
+            self.acting_tenant = Some(self.read_account_tenant(signer));
+            let result = self.apply_policy_op(signer, op);
+            self.acting_tenant = None;
+            result?;
+            self.map_signer_nonce.insert(signer, &(nonce + 1));
+            Ok(())
+        }
+
+        fn insert_group_has(&mut self, group_did: &GroupDID, user_did: &UserDID) ->Result<()> {
+            let user_group = UserGroupEntity{ id: *user_did, is_group: false };
+            let mut vec_user_group = 
+                self.map_group_has.get(self.key(group_did)).unwrap_or_else(Vec::new);
+            if vec_user_group.contains(&user_group) {
+                return Err(Error::GroupHasUserOrGroupAlready);
+            }
+            if let Some(max_members) = self.map_group_max_members.get(self.key(group_did)) {
+                if vec_user_group.len() >= max_members as usize {
+                    return Err(Error::MemberLimitReached);
+                }
+            }
+            vec_user_group.push(user_group);
+            self.map_group_has.insert(self.key(group_did), &vec_user_group);
+            Ok(())
+        }
+
+        fn insert_user_group_belongs(&mut self, user_group_entity: UserGroupEntity, group_did: &GroupDID) -> Result<()> {
+            let mut vec_group = 
+                self.map_user_group_entity_belong.get(self.entity_key(&user_group_entity)).unwrap_or_else(Vec::new);
+            if vec_group.contains(group_did) {
+                return Err(Error::UserOrGroupBelongsGroupAlready);
+            }
+            vec_group.push(*group_did);
+            self.map_user_group_entity_belong.insert(self.entity_key(&user_group_entity), &vec_group);
+            Ok(())
+        }
+
+        // Add user to group
+        #[ink(message)]
+        pub fn add_user_to_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_scope_admin(&self.env().caller(), &AdminScope::Group(group_did))?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddUserToGroup(user_did, group_did))
+        }
+
+        fn apply_add_user_to_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
+            self.end_transfer_grace_for_new_link(&user_did)?;
+            if group_did == user_did ||
+                // user_did is the same as group id
+                self.map_group_has.get(self.key(&user_did)).is_some() ||
+                // group id is the same as user id
+                self.map_user_group_entity_belong.get(self.entity_key(&UserGroupEntity {
+                    id: group_did,
+                    is_group: false
+            })).is_some() {
+                return Err(Error::UserGroupAreSame);
+            }
+            let group_roles = self.get_role(&group_did);
+            self.ensure_ssd_with_roles(&user_did, &group_roles)?;
+            if group_roles.iter().any(|role_did| self.role_limit_reached(role_did, &[user_did])) {
+                return Err(Error::MemberLimitReached);
+            }
+
+            self.insert_group_has(&group_did, &user_did)?;
+            self.insert_user_group_belongs(UserGroupEntity{ id: user_did, is_group: false }, &group_did)?;
+            self.record_edge(EdgeKind::Membership, &user_did, group_did, true);
+            self.keep_link_after_transfer_grace(&user_did, Some(group_did), None);
+            Ok(())
+        }
+
+        fn remove_group_has(&mut self, group_did: &GroupDID, user_did: &UserDID) -> Result<()> {
+            let mut groups = self.map_group_has
+                .get(self.key(group_did))
+                .ok_or(Error::GroupDoesNotExist)?;
+            let index = groups
+                .iter()
+                .position(|r| r.id == *user_did)
+                .ok_or(Error::UserOrGroupDoesNotExistInGroup)?;
+            groups.remove(index);
+            self.map_group_has.insert(self.key(group_did), &groups);
+            Ok(())
+        }
+
+        fn remove_user_group_belongs(&mut self, user_did: &UserDID, group_did: &GroupDID) -> Result<()> {
+            let user = UserGroupEntity{ id: *user_did, is_group: false };
+            let mut groups = self.map_user_group_entity_belong
+                .get(self.entity_key(&user))
+                .ok_or(Error::UserOrGroupDoesNotExist)?;
+            let index = groups
+                .iter()
+                .position(|r| *r == *group_did)
+                .ok_or(Error::GroupDoesNotExistInUserGroup)?;
+            groups.remove(index);
+            self.map_user_group_entity_belong.insert(self.entity_key(&user), &groups);
+            Ok(())
+        }
+
+        // Remove user from group
+        #[ink(message)]
+        pub fn remove_user_from_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_scope_admin(&self.env().caller(), &AdminScope::Group(group_did))?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::RemoveUserFromGroup(user_did, group_did))
+        }
+
+        fn apply_remove_user_from_group(&mut self, user_did: UserDID, group_did: GroupDID) -> Result<()> {
+            self.remove_group_has(&group_did, &user_did)?;
+            self.remove_user_group_belongs(&user_did, &group_did)?;
+            self.record_edge(EdgeKind::Membership, &user_did, group_did, false);
+            Ok(())
+        }
+
+        // Read User Group, return all users in group
+        // Example:
+        // GroupsDID has UserDID1, UserDID2
+        // Return UserDID1, UserDID2
+        #[ink(message)]
+        pub fn read_user_group(&self, group_did: GroupDID) -> Vec<UserDID> {
+            self.map_group_has.get(self.key(&group_did)).unwrap_or_else(Vec::new)
+                .iter()
+                .map(|user| user.id)
+                .collect()
+        }
+
+        fn read_user_belongs(&self, user_did: UserDID) -> Vec<GroupDID> {
+            self.read_user_belongs_in_tenant(&self.caller_tenant(), user_did)
+        }
+
+        fn read_user_belongs_in_tenant(&self, tenant: &TenantId, user_did: UserDID) -> Vec<GroupDID> {
+            let user_entity = UserGroupEntity{ id: user_did, is_group: false };
+            self.read_user_group_entity_belongs(tenant, &user_entity)
+        }
+
+        fn read_group_belongs_in_tenant(&self, tenant: &TenantId, group_did: GroupDID) -> Vec<GroupDID> {
+            let group_entity = UserGroupEntity{ id: group_did, is_group: true };
+            self.read_user_group_entity_belongs(tenant, &group_entity)
+        }
+
+        fn read_user_group_entity_belongs(&self, tenant: &TenantId, user_group_entity: &UserGroupEntity) -> Vec<GroupDID> {
+            self.map_user_group_entity_belong.get((*tenant, user_group_entity.clone())).unwrap_or_else(Vec::new)
+        }
+
+        // Add User or Group to the Role
+        #[ink(message)]
+        pub fn add_user_or_group_to_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_scope_admin(&self.env().caller(), &AdminScope::Role(role_did))?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddUserOrGroupToRole(user_or_group_did, role_did))
+        }
+
+        fn apply_add_user_or_group_to_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
+            self.end_transfer_grace_for_new_link(&user_or_group_did)?;
+            let role = Role{id: role_did};
+            let mut vec_role = self.map_user_group_to_role.get(self.key(&user_or_group_did)).unwrap_or_else(Vec::new);
+            if vec_role.contains(&role) {
+                return Err(Error::UserOrGroupHasRoleAlready);
+            }
+            if self.role_limit_reached(&role_did, &self.role_subjects_of(user_or_group_did)) {
+                return Err(Error::MemberLimitReached);
+            }
+            self.ensure_ssd_with_roles(&user_or_group_did, &[role_did])?;
+            for user_did in self.read_user_group(user_or_group_did) {
+                self.ensure_ssd_with_roles(&user_did, &[role_did])?;
+            }
+            vec_role.push(role);
+            self.map_user_group_to_role.insert(self.key(&user_or_group_did), &vec_role);
+            let mut holders = self.map_role_holders.get(self.key(&role_did)).unwrap_or_default();
+            holders.push(user_or_group_did);
+            self.map_role_holders.insert(self.key(&role_did), &holders);
+            self.record_edge(EdgeKind::Assignment, &user_or_group_did, role_did, true);
+            self.keep_link_after_transfer_grace(&user_or_group_did, None, Some(role_did));
+            Ok(())
+        }
+
+        // Remove User or Group from the Role
+        #[ink(message)]
+        pub fn remove_user_or_group_from_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::RemoveUserOrGroupFromRole(user_or_group_did, role_did))
+        }
+
+        fn apply_remove_user_or_group_from_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
+            let mut roles = self.map_user_group_to_role
+                .get(self.key(&user_or_group_did))
+                .ok_or(Error::UserOrGroupDoesNotExist)?;
+            let index = roles
+                .iter()
+                .position(|r| r.id == role_did)
+                .ok_or(Error::RoleDoesNotExistForUserOrGroup)?;
+            roles.remove(index);
+            self.map_user_group_to_role.insert(self.key(&user_or_group_did), &roles);
+            let mut holders = self.map_role_holders.get(self.key(&role_did)).unwrap_or_default();
+            holders.retain(|holder| *holder != user_or_group_did);
+            self.map_role_holders.insert(self.key(&role_did), &holders);
+            self.map_assignment_schedule.remove((self.key(&user_or_group_did), role_did));
+            self.record_edge(EdgeKind::Assignment, &user_or_group_did, role_did, false);
+            Ok(())
+        }
+
+        fn get_role(&self, user_or_group_did: &UserGroupDID) -> Vec<RoleDID>{
+            self.get_role_in_tenant(&self.caller_tenant(), user_or_group_did)
+        }
+
+        fn get_role_in_tenant(&self, tenant: &TenantId, user_or_group_did: &UserGroupDID) -> Vec<RoleDID>{
+            self.map_user_group_to_role.get(scoped_key(tenant, user_or_group_did)).unwrap_or_else(Vec::new)
+                .iter()
+                .map(|role| role.id)
+                .collect()
+        }
+
+
+        // Move the Group memberships and the direct Roles of the User to another User, e.g. to a replacing device.
+        // With a grace period the old User keeps them for grace_period blocks, and end_transfer_grace removes them afterwards.
+        #[ink(message)]
+        pub fn transfer_subject(&mut self, from_did: UserDID, to_did: UserDID, grace_period: Option<BlockNumber>) -> Result<()> {
+            self.record_message(selector_bytes!("transfer_subject"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if from_did == to_did || rbac.map_group_has.get(rbac.key(&to_did)).is_some() {
+                    return Err(Error::UserGroupAreSame);
+                }
+                let groups = rbac.read_user_belongs(from_did);
+                let roles = rbac.get_role(&from_did);
+                if groups.is_empty() && roles.is_empty() {
+                    return Err(Error::UserOrGroupDoesNotExist);
+                }
+
+                let new_groups = groups
+                    .iter()
+                    .filter(|group_did| !rbac.read_user_group(**group_did).contains(&to_did))
+                    .copied()
+                    .collect::<Vec<_>>();
+                let to_roles = rbac.get_role(&to_did);
+                let new_roles = roles
+                    .iter()
+                    .filter(|role_did| !to_roles.contains(role_did))
+                    .copied()
+                    .collect::<Vec<_>>();
+                let adds = new_groups
+                    .iter()
+                    .map(|group_did| PolicyOp::AddUserToGroup(to_did, *group_did))
+                    .chain(new_roles.iter().map(|role_did| PolicyOp::AddUserOrGroupToRole(to_did, *role_did)))
+                    .collect::<Vec<_>>();
+
+                // Everything is checked before the first operation, so either everything is moved or nothing
+                if adds.iter().any(|op| !rbac.sensitive_dids(op).is_empty()) {
+                    return Err(Error::ApprovalRequired);
+                }
+                let mut gained_roles = new_roles.clone();
+                for group_did in &new_groups {
+                    gained_roles.append(&mut rbac.get_role(group_did));
+                }
+                rbac.ensure_ssd_with_roles(&to_did, &gained_roles)?;
+                // The old User leaves its places to the new one, unless it keeps them for the grace period
+                if grace_period.is_some() {
+                    let group_full = new_groups.iter().any(|group_did| {
+                        let (member_count, max_members) = rbac.read_group_usage(*group_did);
+                        max_members.map_or(false, |max_members| member_count >= max_members)
+                    });
+                    let role_full = gained_roles.iter().any(|role_did| rbac.role_limit_reached(role_did, &[to_did]));
+                    if group_full || role_full {
+                        return Err(Error::MemberLimitReached);
+                    }
+                }
+
+                let schedules = new_roles
+                    .iter()
+                    .filter_map(|role_did| rbac.map_assignment_schedule
+                        .get((rbac.key(&from_did), *role_did))
+                        .map(|schedule_id| (*role_did, schedule_id))
+                    )
+                    .collect::<Vec<_>>();
+                let author = rbac.env().caller();
+                if grace_period.is_none() {
+                    for op in rbac.subject_removals(from_did, &groups, &roles) {
+                        rbac.execute_policy_op(author, op)?;
+                    }
+                }
+                for op in adds {
+                    rbac.execute_policy_op(author, op)?;
+                }
+                for (role_did, schedule_id) in schedules {
+                    rbac.map_assignment_schedule.insert((rbac.key(&to_did), role_did), &schedule_id);
+                }
+
+                let grace_expiry = grace_period.map(|grace_period| rbac.env().block_number().saturating_add(grace_period));
+                if let Some(expiry) = grace_expiry {
+                    // A User transferred again during its grace period loses the earlier links too
+                    let (mut grace_groups, mut grace_roles) = rbac.map_transfer_grace_links
+                        .get(rbac.key(&from_did))
+                        .unwrap_or_default();
+                    grace_groups.extend(groups.iter().filter(|group_did| !grace_groups.contains(group_did)).copied().collect::<Vec<_>>());
+                    grace_roles.extend(roles.iter().filter(|role_did| !grace_roles.contains(role_did)).copied().collect::<Vec<_>>());
+                    rbac.map_transfer_grace.insert(rbac.key(&from_did), &expiry);
+                    rbac.map_transfer_grace_links.insert(rbac.key(&from_did), &(grace_groups, grace_roles));
+                }
+                rbac.env().emit_event(SubjectTransferred { from_did, to_did, grace_expiry });
+                Ok(())
+            })
+        }
+
+        // Remove the Group memberships and the direct Roles of the transferred User after its grace period,
+        // anyone can call it. The User has no transferred access after the grace period even before.
+        #[ink(message)]
+        pub fn end_transfer_grace(&mut self, from_did: UserDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            let expiry = self.read_transfer_grace(from_did).ok_or(Error::TransferGraceDoesNotExist)?;
+            if expiry >= self.env().block_number() {
+                return Err(Error::TransferGraceNotOver);
+            }
+            self.remove_transferred_subject(from_did)
+        }
+
+        // Only the transferred links the User still has are removed, the ones it got afterwards are kept
+        fn remove_transferred_subject(&mut self, from_did: UserDID) -> Result<()> {
+            let (grace_groups, grace_roles) = self.map_transfer_grace_links.get(self.key(&from_did)).unwrap_or_default();
+            let groups = self.read_user_belongs(from_did)
+                .into_iter()
+                .filter(|group_did| grace_groups.contains(group_did))
+                .collect::<Vec<_>>();
+            let roles = self.get_role(&from_did)
+                .into_iter()
+                .filter(|role_did| grace_roles.contains(role_did))
+                .collect::<Vec<_>>();
+            let author = self.env().caller();
+            for op in self.subject_removals(from_did, &groups, &roles) {
+                self.execute_policy_op(author, op)?;
+            }
+            self.map_transfer_grace.remove(self.key(&from_did));
+            self.map_transfer_grace_links.remove(self.key(&from_did));
+            Ok(())
+        }
+
+        // A link added to the transferred User is a new grant, so it is not removed with the transferred ones.
+        // The User can only get a transferred link again after it was removed during the grace period.
+        fn keep_link_after_transfer_grace(&mut self, user_did: &UserGroupDID, group_did: Option<GroupDID>, role_did: Option<RoleDID>) {
+            if let Some((mut grace_groups, mut grace_roles)) = self.map_transfer_grace_links.get(self.key(user_did)) {
+                grace_groups.retain(|did| Some(*did) != group_did);
+                grace_roles.retain(|did| Some(*did) != role_did);
+                self.map_transfer_grace_links.insert(self.key(user_did), &(grace_groups, grace_roles));
+            }
+        }
+
+        // The User given a new Group or Role after its grace period loses the transferred links first,
+        // a new link during the grace period leaves the transfer pending
+        fn end_transfer_grace_for_new_link(&mut self, user_did: &UserDID) -> Result<()> {
+            match self.read_transfer_grace(*user_did) {
+                Some(expiry) if expiry < self.env().block_number() => self.remove_transferred_subject(*user_did),
+                _ => Ok(()),
+            }
+        }
+
+        // Read the last block the transferred User keeps its access at
+        #[ink(message)]
+        pub fn read_transfer_grace(&self, from_did: UserDID) -> Option<BlockNumber> {
+            self.map_transfer_grace.get(self.key(&from_did))
+        }
+
+        fn subject_removals(&self, user_did: UserDID, groups: &[GroupDID], roles: &[RoleDID]) -> Vec<PolicyOp> {
+            groups
+                .iter()
+                .map(|group_did| PolicyOp::RemoveUserFromGroup(user_did, *group_did))
+                .chain(roles.iter().map(|role_did| PolicyOp::RemoveUserOrGroupFromRole(user_did, *role_did)))
+                .collect()
+        }
+
+        // Add Role to the Permission
+        #[ink(message)]
+        pub fn add_role_to_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddRoleToPermission(role_did, permission_did))
+        }
+
+        fn apply_add_role_to_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
+            let permission = Permission{ id: permission_did};
+            let mut vec_permission = self.map_role_to_permission.get(self.key(&role_did)).unwrap_or_else(Vec::new);
+
+            if vec_permission.contains(&permission) {
+                return Err(Error::RoleHasPermissionAlready);
+            }
+            vec_permission.push(permission);
+            self.map_role_to_permission.insert(self.key(&role_did), &vec_permission);
+            self.record_edge(EdgeKind::Permission, &role_did, permission_did, true);
+            Ok(())
+        }
+
+        // Remove Role from the Permission
+        #[ink(message)]
+        pub fn remove_role_from_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::RemoveRoleFromPermission(role_did, permission_did))
+        }
+
+        fn apply_remove_role_from_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
+            let mut perms = self.map_role_to_permission.get(self.key(&role_did)).ok_or(Error::RoleDoesNotExist)?;
+            let index = perms
+                .iter()
+                .position(|r| r.id == permission_did)
+                .ok_or(Error::PermissionNotExistInRole)?;
+
+            perms.remove(index);
+            self.map_role_to_permission.insert(self.key(&role_did), &perms);
+            self.map_permission_condition.remove((self.key(&role_did), permission_did));
+            self.map_permission_schedule.remove((self.key(&role_did), permission_did));
+            self.record_edge(EdgeKind::Permission, &role_did, permission_did, false);
+            Ok(())
+        }
+
+        // Set the condition the Role has the Permission under, None makes it unconditional
+        #[ink(message)]
+        pub fn set_permission_condition(&mut self, role_did: RoleDID, permission_did: PermissionDID, condition: Option<Condition>) -> Result<()> {
+            self.record_message(selector_bytes!("set_permission_condition"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if !rbac.read_permissions(role_did).contains(&permission_did) {
+                    return Err(Error::PermissionNotExistInRole);
+                }
+                match condition {
+                    Some(condition) => {
+                        let comparisons = condition.any_of.iter().map(|all_of| all_of.len()).sum::<usize>();
+                        if condition.any_of.is_empty() ||
+                            condition.any_of.iter().any(|all_of| all_of.is_empty()) ||
+                            comparisons > MAX_CONDITION_COMPARISONS {
+                            return Err(Error::InvalidCondition);
+                        }
+                        rbac.map_permission_condition.insert((rbac.key(&role_did), permission_did), &condition);
+                    }
+                    None => rbac.map_permission_condition.remove((rbac.key(&role_did), permission_did)),
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_permission_condition(&self, role_did: RoleDID, permission_did: PermissionDID) -> Option<Condition> {
+            self.map_permission_condition.get((self.key(&role_did), permission_did))
+        }
+
+        // Set the attribute of the User the conditions can compare, None removes it
+        #[ink(message)]
+        pub fn set_subject_attribute(&mut self, user_did: UserDID, attr_key: AttrKey, value: Option<u64>) -> Result<()> {
+            self.record_message(selector_bytes!("set_subject_attribute"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                match value {
+                    Some(value) => rbac.map_subject_attribute.insert((rbac.key(&user_did), attr_key), &value),
+                    None => rbac.map_subject_attribute.remove((rbac.key(&user_did), attr_key)),
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_subject_attribute(&self, user_did: UserDID, attr_key: AttrKey) -> Option<u64> {
+            self.map_subject_attribute.get((self.key(&user_did), attr_key))
+        }
+
+        fn operand_value(&self, tenant: &TenantId, user_did: &UserDID, operand: &Operand, context: &[(AttrKey, u64)]) -> Option<u64> {
+            match operand {
+                Operand::Const(value) => Some(*value),
+                Operand::Context(attr_key) => context
+                    .iter()
+                    .find(|(key, _)| key == attr_key)
+                    .map(|(_, value)| *value),
+                Operand::Subject(attr_key) => self.map_subject_attribute.get((scoped_key(tenant, user_did), *attr_key)),
+                Operand::BlockTimestamp => Some(self.env().block_timestamp()),
+                Operand::SecondOfDay => Some(self.env().block_timestamp() / 1000 % SECONDS_PER_DAY),
+            }
+        }
+
+        fn ensure_valid_schedule(schedule: &Schedule) -> Result<()> {
+            if schedule.weekdays & 0x7f == 0 ||
+                schedule.windows.is_empty() ||
+                schedule.windows.len() > MAX_SCHEDULE_WINDOWS ||
+                schedule.windows.iter().any(|window| window.start >= window.end || window.end as u64 > SECONDS_PER_DAY) {
+                return Err(Error::InvalidSchedule);
+            }
+            Ok(())
+        }
+
+        // Add the schedule, return its id
+        #[ink(message)]
+        pub fn add_schedule(&mut self, schedule: Schedule) -> Result<ScheduleId> {
+            self.record_message(selector_bytes!("add_schedule"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                Self::ensure_valid_schedule(&schedule)?;
+                let schedule_id = rbac.next_schedule_id;
+                rbac.next_schedule_id += 1;
+                rbac.map_schedule.insert((rbac.caller_tenant(), schedule_id), &schedule);
+                Ok(schedule_id)
+            })
+        }
+
+        // Change the schedule, the assignments and links using it follow the change
+        #[ink(message)]
+        pub fn update_schedule(&mut self, schedule_id: ScheduleId, schedule: Schedule) -> Result<()> {
+            self.record_message(selector_bytes!("update_schedule"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if rbac.read_schedule(schedule_id).is_none() {
+                    return Err(Error::ScheduleDoesNotExist);
+                }
+                Self::ensure_valid_schedule(&schedule)?;
+                rbac.map_schedule.insert((rbac.caller_tenant(), schedule_id), &schedule);
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_schedule(&self, schedule_id: ScheduleId) -> Option<Schedule> {
+            self.map_schedule.get((self.caller_tenant(), schedule_id))
+        }
+
+        // Whether the block time is in the schedule of the tenant, a missing schedule is never active
+        fn schedule_active(&self, tenant: &TenantId, schedule_id: Option<ScheduleId>) -> bool {
+            let schedule = match schedule_id {
+                Some(schedule_id) => match self.map_schedule.get((tenant, schedule_id)) {
+                    Some(schedule) => schedule,
+                    None => return false,
+                },
+                None => return true,
+            };
+            let seconds = self.env().block_timestamp() / 1000;
+            // 1 January 1970 was a Thursday
+            let weekday = (seconds / SECONDS_PER_DAY + 3) % 7;
+            let second_of_day = seconds % SECONDS_PER_DAY;
+            schedule.weekdays & (1 << weekday) != 0 &&
+                schedule.windows
+                    .iter()
+                    .any(|window| window.start as u64 <= second_of_day && second_of_day < window.end as u64)
+        }
+
+        // Limit the User/Group having the Role to the schedule, None removes the limit
+        #[ink(message)]
+        pub fn set_assignment_schedule(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID, schedule_id: Option<ScheduleId>) -> Result<()> {
+            self.record_message(selector_bytes!("set_assignment_schedule"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if !rbac.get_role(&user_or_group_did).contains(&role_did) {
+                    return Err(Error::RoleDoesNotExistForUserOrGroup);
+                }
+                match schedule_id {
+                    Some(schedule_id) => {
+                        if rbac.read_schedule(schedule_id).is_none() {
+                            return Err(Error::ScheduleDoesNotExist);
+                        }
+                        rbac.map_assignment_schedule.insert((rbac.key(&user_or_group_did), role_did), &schedule_id);
+                    }
+                    None => rbac.map_assignment_schedule.remove((rbac.key(&user_or_group_did), role_did)),
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_assignment_schedule(&self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Option<ScheduleId> {
+            self.map_assignment_schedule.get((self.key(&user_or_group_did), role_did))
+        }
+
+        // Limit the Role having the Permission to the schedule, None removes the limit
+        #[ink(message)]
+        pub fn set_permission_schedule(&mut self, role_did: RoleDID, permission_did: PermissionDID, schedule_id: Option<ScheduleId>) -> Result<()> {
+            self.record_message(selector_bytes!("set_permission_schedule"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if !rbac.read_permissions(role_did).contains(&permission_did) {
+                    return Err(Error::PermissionNotExistInRole);
+                }
+                match schedule_id {
+                    Some(schedule_id) => {
+                        if rbac.read_schedule(schedule_id).is_none() {
+                            return Err(Error::ScheduleDoesNotExist);
+                        }
+                        rbac.map_permission_schedule.insert((rbac.key(&role_did), permission_did), &schedule_id);
+                    }
+                    None => rbac.map_permission_schedule.remove((rbac.key(&role_did), permission_did)),
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_permission_schedule(&self, role_did: RoleDID, permission_did: PermissionDID) -> Option<ScheduleId> {
+            self.map_permission_schedule.get((self.key(&role_did), permission_did))
+        }
+
+        // The link without a condition always holds
+        fn condition_holds(&self, tenant: &TenantId, user_did: &UserDID, role_did: &RoleDID, permission_did: &PermissionDID, context: &[(AttrKey, u64)]) -> bool {
+            let condition = match self.map_permission_condition.get((scoped_key(tenant, role_did), *permission_did)) {
+                Some(condition) => condition,
+                None => return true,
+            };
+            condition.any_of.iter().any(|all_of| all_of.iter().all(|comparison| {
+                let left = self.operand_value(tenant, user_did, &comparison.left, context);
+                let right = self.operand_value(tenant, user_did, &comparison.right, context);
+                match (left, right) {
+                    (Some(left), Some(right)) => match comparison.op {
+                        CmpOp::Eq => left == right,
+                        CmpOp::Ne => left != right,
+                        CmpOp::Lt => left < right,
+                        CmpOp::Le => left <= right,
+                        CmpOp::Gt => left > right,
+                        CmpOp::Ge => left >= right,
+                    },
+                    _ => false,
+                }
+            }))
+        }
+
+        // Give the new Role the Permissions of the source Role, on every resource and on the single resources,
+        // with their conditions and schedules
+        #[ink(message)]
+        pub fn clone_role(&mut self, source_role_did: RoleDID, new_role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            let permissions = self.read_permissions(source_role_did);
+            let resources = self.read_role_resources(source_role_did);
+            if permissions.is_empty() && resources.is_empty() {
+                return Err(Error::RoleDoesNotExist);
+            }
+
+            let mut ops = permissions
+                .iter()
+                .map(|permission_did| PolicyOp::AddRoleToPermission(new_role_did, *permission_did))
+                .collect::<Vec<_>>();
+            for resource_did in resources {
+                ops.extend(self.read_permissions_on(source_role_did, resource_did)
+                    .into_iter()
+                    .map(|permission_did| PolicyOp::AddRoleToPermissionOn(new_role_did, permission_did, resource_did))
+                );
+            }
+            self.add_permissions_to_new_role(new_role_did, ops)?;
+
+            // Every copy is recorded like the message making it, as the operations are
+            let author = self.env().caller();
+            for permission_did in permissions {
+                let source = (self.key(&source_role_did), permission_did);
+                let new = (self.key(&new_role_did), permission_did);
+                if let Some(condition) = self.map_permission_condition.get(source) {
+                    self.map_permission_condition.insert(new, &condition);
+                    self.record_change(ChangeOp::Message(selector_bytes!("set_permission_condition")), author);
+                }
+                if let Some(schedule_id) = self.map_permission_schedule.get(source) {
+                    self.map_permission_schedule.insert(new, &schedule_id);
+                    self.record_change(ChangeOp::Message(selector_bytes!("set_permission_schedule")), author);
+                }
+            }
+            Ok(())
+        }
+
+        // Set the Permissions of the role template, no Permissions remove it.
+        // The Roles made from the template before are not changed.
+        #[ink(message)]
+        pub fn set_role_template(&mut self, template_did: TemplateDID, permissions: Vec<PermissionDID>) -> Result<()> {
+            self.record_message(selector_bytes!("set_role_template"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if permissions.is_empty() {
+                    rbac.map_role_template.remove(rbac.key(&template_did));
+                } else {
+                    let mut seen = BTreeSet::new();
+                    let permissions = permissions
+                        .into_iter()
+                        .filter(|permission_did| seen.insert(*permission_did))
+                        .collect::<Vec<_>>();
+                    rbac.map_role_template.insert(rbac.key(&template_did), &permissions);
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_role_template(&self, template_did: TemplateDID) -> Vec<PermissionDID> {
+            self.map_role_template.get(self.key(&template_did)).unwrap_or_default()
+        }
+
+        // Give the new Role the Permissions of the role template
+        #[ink(message)]
+        pub fn instantiate_role_template(&mut self, template_did: TemplateDID, new_role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            let ops = self.map_role_template
+                .get(self.key(&template_did))
+                .ok_or(Error::TemplateDoesNotExist)?
+                .into_iter()
+                .map(|permission_did| PolicyOp::AddRoleToPermission(new_role_did, permission_did))
+                .collect();
+            self.add_permissions_to_new_role(new_role_did, ops)
+        }
+
+        // Everything is checked before the first operation, so the Role gets either all the Permissions or none
+        fn add_permissions_to_new_role(&mut self, role_did: RoleDID, ops: Vec<PolicyOp>) -> Result<()> {
+            if !self.read_permissions(role_did).is_empty() || !self.read_role_resources(role_did).is_empty() {
+                return Err(Error::RoleExistsAlready);
+            }
+            if ops.iter().any(|op| !self.sensitive_dids(op).is_empty()) {
+                return Err(Error::ApprovalRequired);
+            }
+            let author = self.env().caller();
+            for op in ops {
+                self.execute_policy_op(author, op)?;
+            }
+            Ok(())
+        }
+
+        // Add Role to the Permission on the resource only, check_access_on honors it but check_access does not
+        #[ink(message)]
+        pub fn add_role_to_permission_on(&mut self, role_did: RoleDID, permission_did: PermissionDID, resource_did: ResourceDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddRoleToPermissionOn(role_did, permission_did, resource_did))
+        }
+
+        fn apply_add_role_to_permission_on(&mut self, role_did: RoleDID, permission_did: PermissionDID, resource_did: ResourceDID) -> Result<()> {
+            let mut perms = self.read_permissions_on(role_did, resource_did);
+            if perms.contains(&permission_did) {
+                return Err(Error::RoleHasPermissionAlready);
+            }
+            if perms.is_empty() {
+                let mut resources = self.read_role_resources(role_did);
+                resources.push(resource_did);
+                self.map_role_resources.insert(self.key(&role_did), &resources);
+            }
+            perms.push(permission_did);
+            self.map_role_resource_permission.insert((self.key(&role_did), resource_did), &perms);
+            Ok(())
+        }
+
+        // Remove Role from the Permission on the resource
+        #[ink(message)]
+        pub fn remove_role_from_permission_on(&mut self, role_did: RoleDID, permission_did: PermissionDID, resource_did: ResourceDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::RemoveRoleFromPermissionOn(role_did, permission_did, resource_did))
+        }
+
+        fn apply_remove_role_from_permission_on(&mut self, role_did: RoleDID, permission_did: PermissionDID, resource_did: ResourceDID) -> Result<()> {
+            let mut perms = self.read_permissions_on(role_did, resource_did);
+            if !perms.contains(&permission_did) {
+                return Err(Error::PermissionNotExistInRole);
+            }
+            perms.retain(|perm| *perm != permission_did);
+            if perms.is_empty() {
+                let mut resources = self.read_role_resources(role_did);
+                resources.retain(|resource| *resource != resource_did);
+                self.map_role_resources.insert(self.key(&role_did), &resources);
+                self.map_role_resource_permission.remove((self.key(&role_did), resource_did));
+            } else {
+                self.map_role_resource_permission.insert((self.key(&role_did), resource_did), &perms);
+            }
+            Ok(())
+        }
+
+        // Read the Permissions the Role has only on the resource
+        #[ink(message)]
+        pub fn read_permissions_on(&self, role_did: RoleDID, resource_did: ResourceDID) -> Vec<PermissionDID> {
+            self.map_role_resource_permission.get((self.key(&role_did), resource_did)).unwrap_or_default()
+        }
+
+        // Read the resources the Role has Permissions on
+        #[ink(message)]
+        pub fn read_role_resources(&self, role_did: RoleDID) -> Vec<ResourceDID> {
+            self.map_role_resources.get(self.key(&role_did)).unwrap_or_default()
+        }
+
+        // Check Access of the User to the resource, through the Permissions on the resource or its ancestors,
+        // or the Permissions on every resource
+        #[ink(message)]
+        pub fn check_access_on(&self, user_did: UserDID, permission_did: PermissionDID, resource_did: ResourceDID) -> bool {
+            if self.check_access(user_did, permission_did) {
+                return true;
+            }
+            if self.lockdown && !self.is_lockdown_allowed(self.caller_tenant(), permission_did) {
+                return false;
+            }
+            let roles = self.roles_in_tenant(&self.caller_tenant(), user_did, true);
+            core::iter::once(resource_did)
+                .chain(self.read_resource_ancestors(resource_did))
+                .any(|resource_did| roles
+                    .iter()
+                    .any(|&role_did| self.read_permissions_on(role_did, resource_did).contains(&permission_did))
+                )
+        }
+
+        // Make the child resource inherit the Permissions on the parent resource
+        #[ink(message)]
+        pub fn add_child_resource(&mut self, parent_did: ResourceDID, child_did: ResourceDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddChildResource(parent_did, child_did))
+        }
+
+        fn apply_add_child_resource(&mut self, parent_did: ResourceDID, child_did: ResourceDID) -> Result<()> {
+            if self.read_resource_parent(child_did).is_some() {
+                return Err(Error::ResourceHasParentAlready);
+            }
+            let ancestors = self.read_resource_ancestors(parent_did);
+            if parent_did == child_did || ancestors.contains(&child_did) {
+                return Err(Error::ResourceCycle);
+            }
+            // The deepest descendant of the child gets the parent, its ancestors and the levels above it as ancestors
+            if ancestors.len() + 1 + self.read_resource_height(child_did) > MAX_RESOURCE_DEPTH {
+                return Err(Error::ResourceTreeTooDeep);
+            }
+            let mut children = self.read_resource_children(parent_did);
+            children.push(child_did);
+            self.map_resource_children.insert(self.key(&parent_did), &children);
+            self.map_resource_parent.insert(self.key(&child_did), &parent_did);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_child_resource(&mut self, parent_did: ResourceDID, child_did: ResourceDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_admin(&self.env().caller())?;
+            self.apply_policy_op(self.env().caller(), PolicyOp::RemoveChildResource(parent_did, child_did))
+        }
+
+        fn apply_remove_child_resource(&mut self, parent_did: ResourceDID, child_did: ResourceDID) -> Result<()> {
+            if self.read_resource_parent(child_did) != Some(parent_did) {
+                return Err(Error::ResourceNotChild);
+            }
+            let mut children = self.read_resource_children(parent_did);
+            children.retain(|child| *child != child_did);
+            self.map_resource_children.insert(self.key(&parent_did), &children);
+            self.map_resource_parent.remove(self.key(&child_did));
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn read_resource_parent(&self, resource_did: ResourceDID) -> Option<ResourceDID> {
+            self.map_resource_parent.get(self.key(&resource_did))
+        }
+
+        #[ink(message)]
+        pub fn read_resource_children(&self, resource_did: ResourceDID) -> Vec<ResourceDID> {
+            self.map_resource_children.get(self.key(&resource_did)).unwrap_or_default()
+        }
+
+        // Read the ancestors of the resource, starting with its parent, at most MAX_RESOURCE_DEPTH of them
+        fn read_resource_ancestors(&self, resource_did: ResourceDID) -> Vec<ResourceDID> {
+            let mut ancestors = Vec::new();
+            let mut current = resource_did;
+            while let Some(parent_did) = self.read_resource_parent(current) {
+                if ancestors.len() >= MAX_RESOURCE_DEPTH {
+                    break;
+                }
+                ancestors.push(parent_did);
+                current = parent_did;
+            }
+            ancestors
+        }
+
+        // Number of the levels below the resource, 0 for a resource without children
+        fn read_resource_height(&self, resource_did: ResourceDID) -> usize {
+            let mut height = 0;
+            let mut level = self.read_resource_children(resource_did);
+            while !level.is_empty() {
+                height += 1;
+                level = level
+                    .iter()
+                    .flat_map(|child_did| self.read_resource_children(*child_did))
+                    .collect();
+            }
+            height
+        }
+
+        // Read all the descendants of the resource, level by level
+        #[ink(message)]
+        pub fn read_resource_subtree(&self, resource_did: ResourceDID) -> Vec<ResourceDID> {
+            let mut subtree = self.read_resource_children(resource_did);
+            let mut index = 0;
+            while index < subtree.len() {
+                let mut children = self.read_resource_children(subtree[index]);
+                subtree.append(&mut children);
+                index += 1;
+            }
+            subtree
+        }
+          
+        // Increase the policy version of the tenant and keep the record of the change
+        fn record_change(&mut self, op: ChangeOp, caller: AccountId) {
+            let tenant = self.caller_tenant();
+            let version = self.policy_version() + 1;
+            self.map_policy_version.insert(tenant, &version);
+            self.map_change.insert((tenant, version % CHANGE_LOG_CAPACITY), &ChangeRecord {
+                op,
+                caller,
+                block_number: self.env().block_number(),
+                version,
+            });
+        }
+
+        // Run the message with the selector, and record its change if it succeeds.
+        // Every message changing the policy or its administration outside the policy operations runs through it.
+        // Using the access, e.g. tokens, sessions, break-glass, quotas, requests, proposals and the audit log, is not a change.
+        fn record_message<T>(&mut self, selector: [u8; 4], message: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+            let result = message(self)?;
+            self.record_change(ChangeOp::Message(selector), self.env().caller());
+            Ok(result)
+        }
+
+        // Read at most MAX_CHANGE_PAGE records of the changes after the version, oldest first.
+        // Fails if the changes right after the version are not kept anymore, the caches have to be rebuilt then.
+        #[ink(message)]
+        pub fn read_changes_since(&self, version: u64) -> Result<Vec<ChangeRecord>> {
+            let tenant = self.caller_tenant();
+            let policy_version = self.policy_version();
+            if version.saturating_add(CHANGE_LOG_CAPACITY) < policy_version {
+                return Err(Error::ChangesPruned);
+            }
+            let end = policy_version.min(version.saturating_add(MAX_CHANGE_PAGE));
+            Ok((version.saturating_add(1)..=end)
+                .filter_map(|version| self.map_change.get((tenant, version % CHANGE_LOG_CAPACITY)))
+                .collect())
+        }
+
+        // Open the link to the target in the history, or close it if removed.
+        // The links removed before the retention are dropped, and so are the oldest ones over MAX_REMOVED_EDGES.
+        fn record_edge(&mut self, kind: EdgeKind, did: &DIDType, target: DIDType, added: bool) {
+            let now = self.env().block_number();
+            let retention = self.history_retention;
+            let key = (kind, self.key(did));
+            let mut edges = self.map_edge_history.get(key).unwrap_or_default();
+            edges.retain(|edge| edge.until.map_or(true, |until| until.saturating_add(retention) >= now));
+            if added {
+                edges.push(EdgeInterval { target, from: now, until: None });
+            } else if let Some(edge) = edges.iter_mut().find(|edge| edge.target == target && edge.until.is_none()) {
+                edge.until = Some(now);
+            }
+
+            let mut removed = edges.iter().filter(|edge| edge.until.is_some()).count();
+            let mut history_since = None;
+            while removed > MAX_REMOVED_EDGES {
+                let (index, until) = edges
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, edge)| edge.until.map(|until| (index, until)))
+                    .min_by_key(|(_, until)| *until)
+                    .expect("removed links exist");
+                edges.remove(index);
+                history_since = Some(until);
+                removed -= 1;
+            }
+            if let Some(until) = history_since {
+                self.map_edge_history_since.insert(key, &until);
+            }
+            self.map_edge_history.insert(key, &edges);
+        }
+
+        // Read the links of the DID existing at the end of the block, fails if some were dropped from the history
+        fn edge_targets_at(&self, tenant: &TenantId, kind: EdgeKind, did: &DIDType, block_number: BlockNumber) -> Result<Vec<DIDType>> {
+            let key = (kind, scoped_key(tenant, did));
+            if block_number < self.map_edge_history_since.get(key).unwrap_or_default() {
+                return Err(Error::HistoryNotKept);
+            }
+            Ok(self.map_edge_history.get(key).unwrap_or_default()
+                .into_iter()
+                .filter(|edge| edge.from <= block_number && edge.until.map_or(true, |until| block_number < until))
+                .map(|edge| edge.target)
+                .collect())
+        }
+
+        // Check the User had the Permission through its Groups and Roles at the end of the block.
+        // Only the links are kept in the history, so the schedules, conditions, lockdown and break-glass are not considered.
+        #[ink(message)]
+        pub fn check_access_at(&self, user_did: UserDID, permission_did: PermissionDID, block_number: BlockNumber) -> Result<bool> {
+            let now = self.env().block_number();
+            let oldest = self.history_kept_since.max(now.saturating_sub(self.history_retention));
+            if block_number > now || block_number < oldest {
+                return Err(Error::HistoryNotKept);
+            }
+            let tenant = self.caller_tenant();
+            let mut holders = self.edge_targets_at(&tenant, EdgeKind::Membership, &user_did, block_number)?;
+            holders.push(user_did);
+            for did in holders {
+                for role_did in self.edge_targets_at(&tenant, EdgeKind::Assignment, &did, block_number)? {
+                    if self.edge_targets_at(&tenant, EdgeKind::Permission, &role_did, block_number)?.contains(&permission_did) {
+                        return Ok(true);
+                    }
+                }
+            }
+            Ok(false)
+        }
+
+        // Set how many blocks back check_access_at can look, for every tenant. Only the owner can call it.
+        // The history dropped with the old retention is gone, so an extended retention fills up over time.
+        #[ink(message)]
+        pub fn set_history_retention(&mut self, retention: BlockNumber) -> Result<()> {
+            self.record_message(selector_bytes!("set_history_retention"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                let oldest = rbac.env().block_number().saturating_sub(rbac.history_retention);
+                rbac.history_kept_since = rbac.history_kept_since.max(oldest);
+                rbac.history_retention = retention;
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn history_retention(&self) -> BlockNumber {
+            self.history_retention
+        }
+
+        // Read the current policy version of the tenant
+        #[ink(message)]
+        pub fn policy_version(&self) -> u64 {
+            self.map_policy_version.get(self.caller_tenant()).unwrap_or_default()
+        }
+
+        fn is_token_revoked(&self, tenant: &TenantId, hash: &TokenHash) -> bool {
+            self.map_token_revoked.get((tenant, hash)).is_some()
+        }
+
+        // Issue a capability token for the User and the Permission,
+        // which is valid until the expiry block if check_access allows it now.
+        // Only the admins, the consumers and the account bound to the User can issue it.
+        #[ink(message)]
+        pub fn issue_token(&mut self, user_did: UserDID, permission_did: PermissionDID, expiry: BlockNumber) -> Result<CapabilityToken> {
+            self.ensure_not_paused()?;
+            self.ensure_consumer(&user_did)?;
+            if expiry < self.env().block_number() {
+                return Err(Error::TokenExpired);
+            }
+            if !self.check_access(user_did, permission_did) {
+                return Err(Error::AccessNotGranted);
+            }
+
+            let tenant = self.caller_tenant();
+            let token = CapabilityToken {
+                tenant,
+                user_did,
+                permission_did,
+                expiry,
+                nonce: self.token_nonce,
+                policy_version: self.policy_version(),
+            };
+            self.token_nonce += 1;
+
+            let hash = token.hash();
+            self.map_token_issued.insert((tenant, hash), &expiry);
+            let count = self.read_issued_token_count(tenant);
+            self.map_issued_token.insert((tenant, count), &hash);
+            self.map_issued_token_count.insert(tenant, &(count + 1));
+            self.env().emit_event(TokenIssued { hash, token: token.clone() });
+            Ok(token)
+        }
+
+        // Verify the token was issued here, is neither expired nor revoked,
+        // and the User still has the Permission in the tenant of the token
+        #[ink(message)]
+        pub fn verify_token(&self, token: CapabilityToken) -> bool {
+            let hash = token.hash();
+            self.map_token_issued.get((token.tenant, hash)).is_some() &&
+                token.expiry >= self.env().block_number() &&
+                !self.is_token_revoked(&token.tenant, &hash) &&
+                self.check_access_in_tenant(token.tenant, token.user_did, token.permission_did)
+        }
+
+        // Revoke the token of the tenant before its expiry
+        #[ink(message)]
+        pub fn revoke_token(&mut self, token: CapabilityToken) -> Result<()> {
+            self.record_message(selector_bytes!("revoke_token"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.ensure_caller_tenant(&token.tenant)?;
+                let hash = token.hash();
+                if rbac.map_token_issued.get((token.tenant, hash)).is_none() {
+                    return Err(Error::TokenDoesNotExist);
+                }
+                if rbac.is_token_revoked(&token.tenant, &hash) {
+                    return Err(Error::TokenRevokedAlready);
+                }
+                if token.expiry < rbac.env().block_number() {
+                    return Err(Error::TokenExpired);
+                }
+
+                let count = rbac.read_revoked_token_count(token.tenant);
+                rbac.map_token_revoked.insert((token.tenant, hash), &());
+                rbac.map_revoked_token.insert((token.tenant, count), &hash);
+                rbac.map_revoked_token_count.insert(token.tenant, &(count + 1));
+                rbac.env().emit_event(TokenRevoked { hash });
+                Ok(())
+            })
+        }
+
+        // Read the number of the tokens of the tenant ever issued
+        #[ink(message)]
+        pub fn read_issued_token_count(&self, tenant: TenantId) -> u64 {
+            self.map_issued_token_count.get(tenant).unwrap_or_default()
+        }
+
+        // Read at most limit hashes of the tokens of the tenant issued from the sequence number on,
+        // so the off-chain verifiers only accept the tokens issued here
+        #[ink(message)]
+        pub fn read_issued_tokens(&self, tenant: TenantId, from: u64, limit: u32) -> Vec<TokenHash> {
+            let end = self.read_issued_token_count(tenant)
+                .min(from.saturating_add(limit.min(MAX_ISSUED_TOKEN_PAGE) as u64));
+            (from..end)
+                .filter_map(|seq| self.map_issued_token.get((tenant, seq)))
+                .collect()
+        }
+
+        // Read the number of the tokens of the tenant ever revoked
+        #[ink(message)]
+        pub fn read_revoked_token_count(&self, tenant: TenantId) -> u64 {
+            self.map_revoked_token_count.get(tenant).unwrap_or_default()
+        }
+
+        // Read at most limit hashes of the revocation list of the tenant from the sequence number on,
+        // so the off-chain verifiers can sync the list incrementally
+        #[ink(message)]
+        pub fn read_revoked_tokens(&self, tenant: TenantId, from: u64, limit: u32) -> Vec<TokenHash> {
+            let end = self.read_revoked_token_count(tenant)
+                .min(from.saturating_add(limit.min(MAX_REVOKED_TOKEN_PAGE) as u64));
+            (from..end)
+                .filter_map(|seq| self.map_revoked_token.get((tenant, seq)))
+                .collect()
+        }
+
+        // Check Access like check_access, and record the decision in the audit log of the tenant
+        #[ink(message)]
+        pub fn check_and_record_access(&mut self, user_did: UserDID, permission_did: PermissionDID) -> Result<bool> {
+            self.ensure_not_paused()?;
+            self.ensure_consumer(&user_did)?;
+            let caller = self.env().caller();
+            let granted = self.check_access(user_did, permission_did);
+
+            let tenant = self.caller_tenant();
+            let count = self.read_audit_count();
+            self.map_audit_entry.insert((tenant, count % AUDIT_LOG_CAPACITY), &AuditEntry {
+                caller,
+                user_did,
+                permission_did,
+                granted,
+                block_number: self.env().block_number(),
+            });
+            self.map_audit_count.insert(tenant, &(count + 1));
+            self.env().emit_event(AccessChecked { user_did, permission_did, caller, granted });
+            Ok(granted)
+        }
+
+        // Read the number of the entries ever recorded in the audit log of the tenant
+        #[ink(message)]
+        pub fn read_audit_count(&self) -> u64 {
+            self.map_audit_count.get(self.caller_tenant()).unwrap_or_default()
+        }
+
+        // Read at most limit entries of the audit log from the sequence number on, with their sequence numbers.
+        // The overwritten entries are skipped.
+        #[ink(message)]
+        pub fn read_audit_log(&self, from: u64, limit: u32) -> Vec<(u64, AuditEntry)> {
+            let tenant = self.caller_tenant();
+            let count = self.read_audit_count();
+            let start = from.max(count.saturating_sub(AUDIT_LOG_CAPACITY));
+            let end = count.min(start.saturating_add(limit.min(MAX_AUDIT_PAGE) as u64));
+            (start..end)
+                .filter_map(|seq| self.map_audit_entry
+                    .get((tenant, seq % AUDIT_LOG_CAPACITY))
+                    .map(|entry| (seq, entry))
+                )
+                .collect()
+        }
+
+        // Let the account consume the access of any User, only the admins and the bound account can otherwise
+        #[ink(message)]
+        pub fn set_consumer(&mut self, account: AccountId, consumer: bool) -> Result<()> {
+            self.record_message(selector_bytes!("set_consumer"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.ensure_caller_tenant(&rbac.read_account_tenant(account))?;
+                if consumer {
+                    rbac.map_consumer.insert((rbac.caller_tenant(), account), &true);
+                } else {
+                    rbac.map_consumer.remove((rbac.caller_tenant(), account));
+                }
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn is_consumer(&self, account: AccountId) -> bool {
+            self.map_consumer.get((self.caller_tenant(), account)).unwrap_or_default()
+        }
+
+        // Only the admins, the consumers and the account bound to the User can use its access
+        fn ensure_consumer(&self, user_did: &UserDID) -> Result<()> {
+            let caller = self.env().caller();
+            if !self.is_admin(caller) &&
+                !self.is_consumer(caller) &&
+                self.map_account_did.get(caller) != Some(*user_did) {
+                return Err(Error::NotConsumer);
+            }
+            Ok(())
+        }
+
+        // Set how many times the User can consume the Permission, None removes the limit
+        #[ink(message)]
+        pub fn set_access_quota(&mut self, user_did: UserDID, permission_did: PermissionDID, quota: Option<u32>) -> Result<()> {
+            self.record_message(selector_bytes!("set_access_quota"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                match quota {
+                    Some(quota) => rbac.map_access_quota.insert((rbac.key(&user_did), permission_did), &quota),
+                    None => rbac.map_access_quota.remove((rbac.key(&user_did), permission_did)),
+                }
+                Ok(())
+            })
+        }
+
+        // Read how many times the User can still consume the Permission, None if it is not limited
+        #[ink(message)]
+        pub fn read_access_quota(&self, user_did: UserDID, permission_did: PermissionDID) -> Option<u32> {
+            self.map_access_quota.get((self.key(&user_did), permission_did))
+        }
+
+        // Use the access of the User once, it fails if check_access does or the quota is used up
+        #[ink(message)]
+        pub fn consume_access(&mut self, user_did: UserDID, permission_did: PermissionDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            self.ensure_consumer(&user_did)?;
+            if !self.check_access(user_did, permission_did) {
+                return Err(Error::AccessNotGranted);
+            }
+            let remaining = match self.read_access_quota(user_did, permission_did) {
+                Some(0) => return Err(Error::QuotaExhausted),
+                Some(quota) => {
+                    self.map_access_quota.insert((self.key(&user_did), permission_did), &(quota - 1));
+                    Some(quota - 1)
+                }
+                None => None,
+            };
+            self.env().emit_event(AccessConsumed { user_did, permission_did, remaining });
+            Ok(())
+        }
+
+        // Bind the account to the UserDID, so the account acts as this User
+        #[ink(message)]
+        pub fn bind_account(&mut self, account: AccountId, user_did: UserDID) -> Result<()> {
+            self.record_message(selector_bytes!("bind_account"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.ensure_caller_tenant(&rbac.read_account_tenant(account))?;
+                rbac.map_account_did.insert(account, &user_did);
+                Ok(())
+            })
+        }
+
+        // Unbind the account from its UserDID
+        #[ink(message)]
+        pub fn unbind_account(&mut self, account: AccountId) -> Result<()> {
+            self.record_message(selector_bytes!("unbind_account"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.ensure_caller_tenant(&rbac.read_account_tenant(account))?;
+                if rbac.map_account_did.get(account).is_none() {
+                    return Err(Error::AccountNotBound);
+                }
+                rbac.map_account_did.remove(account);
+                Ok(())
+            })
+        }
+
+        // Read the UserDID the account is bound to
+        #[ink(message)]
+        pub fn read_account_did(&self, account: AccountId) -> Option<UserDID> {
+            self.map_account_did.get(account)
+        }
+
+        // Allow the Users having the Permission to call the selector of the callee through execute_if_permitted
+        #[ink(message)]
+        pub fn allow_call(&mut self, permission_did: PermissionDID, callee: AccountId, selector: [u8; 4]) -> Result<()> {
+            self.record_message(selector_bytes!("allow_call"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.map_call_permission.insert((rbac.caller_tenant(), callee, selector), &permission_did);
+                Ok(())
+            })
+        }
+
+        // Disallow calling the selector of the callee through execute_if_permitted
+        #[ink(message)]
+        pub fn disallow_call(&mut self, callee: AccountId, selector: [u8; 4]) -> Result<()> {
+            self.record_message(selector_bytes!("disallow_call"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                if rbac.map_call_permission.get((rbac.caller_tenant(), callee, selector)).is_none() {
+                    return Err(Error::CallNotAllowed);
+                }
+                rbac.map_call_permission.remove((rbac.caller_tenant(), callee, selector));
+                Ok(())
+            })
+        }
+
+        // Read the Permission required to call the selector of the callee
+        #[ink(message)]
+        pub fn read_call_permission(&self, callee: AccountId, selector: [u8; 4]) -> Option<PermissionDID> {
+            self.map_call_permission.get((self.caller_tenant(), callee, selector))
+        }
+
+        fn sod_storage(&mut self, kind: SodKind) -> (
+            &mut Mapping<ConstraintId, SsdConstraint>,
+            &mut Mapping<ScopedDID, Vec<ConstraintId>>,
+            &mut ConstraintId,
+        ) {
+            match kind {
+                SodKind::Static => (
+                    &mut self.map_ssd_constraint,
+                    &mut self.map_role_ssd_constraints,
+                    &mut self.next_ssd_constraint_id,
+                ),
+                SodKind::Dynamic => (
+                    &mut self.map_dsd_constraint,
+                    &mut self.map_role_dsd_constraints,
+                    &mut self.next_dsd_constraint_id,
+                ),
+            }
+        }
+
+        fn insert_sod_constraint(&mut self, kind: SodKind, roles: Vec<RoleDID>, cardinality: u32) -> Result<ConstraintId> {
+            let roles = roles.into_iter().collect::<BTreeSet<_>>();
+            if cardinality < 2 || cardinality as usize > roles.len() {
+                return Err(Error::InvalidSsdConstraint);
+            }
+            if matches!(kind, SodKind::Static) && self.roles_held_together(&roles, cardinality) {
+                return Err(Error::SsdConstraintViolatedAlready);
+            }
+
+            let tenant = self.caller_tenant();
+            let (constraints, role_constraints, next_id) = self.sod_storage(kind);
+            let id = *next_id;
+            *next_id += 1;
+            for role_did in roles.iter() {
+                let mut ids = role_constraints.get(scoped_key(&tenant, role_did)).unwrap_or_default();
+                ids.push(id);
+                role_constraints.insert(scoped_key(&tenant, role_did), &ids);
+            }
+            constraints.insert(id, &SsdConstraint {
+                tenant,
+                roles: roles.into_iter().collect(),
+                cardinality,
+            });
+            Ok(id)
+        }
+
+        fn delete_sod_constraint(&mut self, kind: SodKind, id: ConstraintId) -> Result<()> {
+            let tenant = self.caller_tenant();
+            let (constraints, role_constraints, _) = self.sod_storage(kind);
+            let constraint = constraints
+                .get(id)
+                .filter(|constraint| constraint.tenant == tenant)
+                .ok_or(Error::SsdConstraintDoesNotExist)?;
+            for role_did in constraint.roles.iter() {
+                let mut ids = role_constraints.get(scoped_key(&tenant, role_did)).unwrap_or_default();
+                ids.retain(|constraint_id| *constraint_id != id);
+                role_constraints.insert(scoped_key(&tenant, role_did), &ids);
+            }
+            constraints.remove(id);
+            Ok(())
+        }
+
+        // Find a constraint the roles violate together
+        fn violated_sod_constraint(&self, kind: SodKind, roles: &BTreeSet<RoleDID>) -> Option<ConstraintId> {
+            let (constraints, role_constraints) = match kind {
+                SodKind::Static => (&self.map_ssd_constraint, &self.map_role_ssd_constraints),
+                SodKind::Dynamic => (&self.map_dsd_constraint, &self.map_role_dsd_constraints),
+            };
+            roles
+                .iter()
+                .flat_map(|role_did| role_constraints.get(self.key(role_did)).unwrap_or_default())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .find(|id| {
+                    constraints.get(id).map_or(false, |constraint| {
+                        let held = constraint.roles
+                            .iter()
+                            .filter(|role_did| roles.contains(*role_did))
+                            .count();
+                        held >= constraint.cardinality as usize
+                    })
+                })
+        }
+
+        // Whether a User/Group has cardinality or more of the roles already, directly or through its Groups
+        fn roles_held_together(&self, roles: &BTreeSet<RoleDID>, cardinality: u32) -> bool {
+            roles
+                .iter()
+                .flat_map(|role_did| self.map_role_holders.get(self.key(role_did)).unwrap_or_default())
+                .flat_map(|holder| {
+                    let mut subjects = self.read_user_group(holder);
+                    subjects.push(holder);
+                    subjects
+                })
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .any(|did| {
+                    let held = self.read_user_or_group_roles(did)
+                        .iter()
+                        .filter(|role_did| roles.contains(*role_did))
+                        .count();
+                    held >= cardinality as usize
+                })
+        }
+
+        // Add a static separation of duty constraint, return its id
+        #[ink(message)]
+        pub fn add_ssd_constraint(&mut self, roles: Vec<RoleDID>, cardinality: u32) -> Result<ConstraintId> {
+            self.record_message(selector_bytes!("add_ssd_constraint"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                let id = rbac.insert_sod_constraint(SodKind::Static, roles, cardinality)?;
+                Ok(id)
+            })
+        }
+
+        // Remove the static separation of duty constraint
+        #[ink(message)]
+        pub fn remove_ssd_constraint(&mut self, id: ConstraintId) -> Result<()> {
+            self.record_message(selector_bytes!("remove_ssd_constraint"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.delete_sod_constraint(SodKind::Static, id)?;
+                Ok(())
+            })
+        }
+
+        // Read the static separation of duty constraint
+        #[ink(message)]
+        pub fn read_ssd_constraint(&self, id: ConstraintId) -> Option<SsdConstraint> {
+            self.map_ssd_constraint
+                .get(id)
+                .filter(|constraint| constraint.tenant == self.caller_tenant())
+        }
+
+        // Read the ids of the static separation of duty constraints having the Role
+        #[ink(message)]
+        pub fn read_role_ssd_constraints(&self, role_did: RoleDID) -> Vec<ConstraintId> {
+            self.map_role_ssd_constraints.get(self.key(&role_did)).unwrap_or_default()
+        }
+
+        // Check the User/Group would not violate any static constraint with the roles added to its roles
+        fn ensure_ssd_with_roles(&self, user_or_group_did: &UserGroupDID, new_roles: &[RoleDID]) -> Result<()> {
+            let mut roles = self.read_user_or_group_roles(*user_or_group_did)
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+            roles.extend(new_roles.iter().copied());
+
+            match self.violated_sod_constraint(SodKind::Static, &roles) {
+                Some(id) => Err(Error::SsdConstraintViolated(id)),
+                None => Ok(()),
+            }
+        }
+
+        // Add a dynamic separation of duty constraint, return its id.
+        // Nobody may activate `cardinality` or more of the roles in the same session.
+        #[ink(message)]
+        pub fn add_dsd_constraint(&mut self, roles: Vec<RoleDID>, cardinality: u32) -> Result<ConstraintId> {
+            self.record_message(selector_bytes!("add_dsd_constraint"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                let id = rbac.insert_sod_constraint(SodKind::Dynamic, roles, cardinality)?;
+                Ok(id)
+            })
+        }
+
+        // Remove the dynamic separation of duty constraint
+        #[ink(message)]
+        pub fn remove_dsd_constraint(&mut self, id: ConstraintId) -> Result<()> {
+            self.record_message(selector_bytes!("remove_dsd_constraint"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.delete_sod_constraint(SodKind::Dynamic, id)?;
+                Ok(())
+            })
+        }
+
+        // Read the dynamic separation of duty constraint
+        #[ink(message)]
+        pub fn read_dsd_constraint(&self, id: ConstraintId) -> Option<SsdConstraint> {
+            self.map_dsd_constraint
+                .get(id)
+                .filter(|constraint| constraint.tenant == self.caller_tenant())
+        }
+
+        // Read the ids of the dynamic separation of duty constraints having the Role
+        #[ink(message)]
+        pub fn read_role_dsd_constraints(&self, role_did: RoleDID) -> Vec<ConstraintId> {
+            self.map_role_dsd_constraints.get(self.key(&role_did)).unwrap_or_default()
+        }
+
+        // Set the maximum number of Users having the Role, directly or through the Groups, None removes the limit.
+        // Lowering it under the current number only stops the new assignments.
+        #[ink(message)]
+        pub fn set_role_max_members(&mut self, role_did: RoleDID, max_members: Option<u32>) -> Result<()> {
+            self.record_message(selector_bytes!("set_role_max_members"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                match max_members {
+                    Some(max_members) => rbac.map_role_max_members.insert(rbac.key(&role_did), &max_members),
+                    None => rbac.map_role_max_members.remove(rbac.key(&role_did)),
+                }
+                Ok(())
+            })
+        }
+
+        // Set the maximum number of Users in the Group, None removes the limit.
+        // Lowering it under the current number only stops the new members.
+        #[ink(message)]
+        pub fn set_group_max_members(&mut self, group_did: GroupDID, max_members: Option<u32>) -> Result<()> {
+            self.record_message(selector_bytes!("set_group_max_members"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                match max_members {
+                    Some(max_members) => rbac.map_group_max_members.insert(rbac.key(&group_did), &max_members),
+                    None => rbac.map_group_max_members.remove(rbac.key(&group_did)),
+                }
+                Ok(())
+            })
+        }
+
+        // Read the number of Users having the Role, directly or through the Groups, and its limit
+        #[ink(message)]
+        pub fn read_role_usage(&self, role_did: RoleDID) -> (u32, Option<u32>) {
+            (
+                self.role_subjects(&role_did).len() as u32,
+                self.map_role_max_members.get(self.key(&role_did)),
+            )
+        }
+
+        // The Users a Role given to the User/Group reaches, the members for a Group
+        fn role_subjects_of(&self, user_or_group_did: UserGroupDID) -> Vec<UserGroupDID> {
+            match self.map_group_has.get(self.key(&user_or_group_did)) {
+                Some(members) => members.iter().map(|member| member.id).collect(),
+                None => vec![user_or_group_did],
+            }
+        }
+
+        // The Users having the Role, directly or through the Groups
+        fn role_subjects(&self, role_did: &RoleDID) -> BTreeSet<UserGroupDID> {
+            self.map_role_holders
+                .get(self.key(role_did))
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|holder| self.role_subjects_of(holder))
+                .collect()
+        }
+
+        // Whether the Role has no room for the new Users not having it yet
+        fn role_limit_reached(&self, role_did: &RoleDID, new_subjects: &[UserGroupDID]) -> bool {
+            let max_members = match self.map_role_max_members.get(self.key(role_did)) {
+                Some(max_members) => max_members,
+                None => return false,
+            };
+            let subjects = self.role_subjects(role_did);
+            let added = new_subjects
+                .iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter(|did| !subjects.contains(*did))
+                .count();
+            added > 0 && subjects.len() + added > max_members as usize
+        }
+
+        // Read the number of Users in the Group and its limit
+        #[ink(message)]
+        pub fn read_group_usage(&self, group_did: GroupDID) -> (u32, Option<u32>) {
+            (
+                self.read_user_group(group_did).len() as u32,
+                self.map_group_max_members.get(self.key(&group_did)),
+            )
+        }
+
+        // Set the Permission whose holders can approve the requests for the Role, None leaves it to the admins
+        #[ink(message)]
+        pub fn set_role_manager_permission(&mut self, role_did: RoleDID, permission_did: Option<PermissionDID>) -> Result<()> {
+            self.record_message(selector_bytes!("set_role_manager_permission"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                match permission_did {
+                    Some(permission_did) => rbac.map_role_manager_permission.insert(rbac.key(&role_did), &permission_did),
+                    None => rbac.map_role_manager_permission.remove(rbac.key(&role_did)),
+                }
+                Ok(())
+            })
+        }
+
+        // Read the Permission whose holders can approve the requests for the Role
+        #[ink(message)]
+        pub fn read_role_manager_permission(&self, role_did: RoleDID) -> Option<PermissionDID> {
+            self.map_role_manager_permission.get(self.key(&role_did))
+        }
+
+        // Admins can manage every Role, the others need the manager Permission of the Role
+        fn ensure_role_manager(&self, account: &AccountId, role_did: &RoleDID) -> Result<()> {
+            if self.is_admin(*account) {
+                return Ok(());
+            }
+            let manager = self.map_role_manager_permission.get(self.key(role_did))
+                .zip(self.map_account_did.get(account))
+                .map_or(false, |(permission_did, user_did)| self.check_access(user_did, permission_did));
+            if !manager {
+                return Err(Error::NotRoleManager);
+            }
+            Ok(())
+        }
+
+        // Set how many blocks the new requests can be answered in.
+        // It applies to every tenant, so only the owner can call it.
+        #[ink(message)]
+        pub fn set_request_lifetime(&mut self, lifetime: BlockNumber) -> Result<()> {
+            self.record_message(selector_bytes!("set_request_lifetime"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.request_lifetime = lifetime;
+                Ok(())
+            })
+        }
+
+        // Read how many blocks the new requests can be answered in
+        #[ink(message)]
+        pub fn request_lifetime(&self) -> BlockNumber {
+            self.request_lifetime
+        }
+
+        // Request the Role for the User the caller is bound to
+        #[ink(message)]
+        pub fn request_role(&mut self, role_did: RoleDID, justification_hash: [u8; 32]) -> Result<RequestId> {
+            self.ensure_not_paused()?;
+            let user_did = self.map_account_did
+                .get(self.env().caller())
+                .ok_or(Error::AccountNotBound)?;
+            if self.get_role(&user_did).contains(&role_did) {
+                return Err(Error::UserOrGroupHasRoleAlready);
+            }
+            let mut request_ids = self.read_role_requests(role_did);
+            let own_requests = request_ids
+                .iter()
+                .filter_map(|id| self.map_access_request.get(id).map(|request| (*id, request)))
+                .filter(|(_, request)| request.user_did == user_did)
+                .collect::<Vec<_>>();
+            if own_requests.iter().any(|(_, request)| request.expiry >= self.env().block_number()) {
+                return Err(Error::RequestExistsAlready);
+            }
+            // The expired requests of the User are replaced by the new one
+            for (id, _) in own_requests {
+                request_ids.retain(|request_id| *request_id != id);
+                self.map_access_request.remove(id);
+                self.env().emit_event(RequestExpired { request_id: id });
+            }
+
+            let request_id = self.next_request_id;
+            self.next_request_id += 1;
+            self.map_access_request.insert(request_id, &AccessRequest {
+                tenant: self.caller_tenant(),
+                user_did,
+                role_did,
+                justification_hash,
+                expiry: self.env().block_number().saturating_add(self.request_lifetime),
+            });
+            request_ids.push(request_id);
+            self.map_role_requests.insert(self.key(&role_did), &request_ids);
+            self.env().emit_event(RoleRequested {
+                request_id,
+                user_did,
+                role_did,
+                justification_hash,
+            });
+            Ok(request_id)
+        }
+
+        fn remove_request(&mut self, request_id: RequestId, request: &AccessRequest) {
+            let mut request_ids = self.read_role_requests(request.role_did);
+            request_ids.retain(|id| *id != request_id);
+            self.map_role_requests.insert(self.key(&request.role_did), &request_ids);
+            self.map_access_request.remove(request_id);
+        }
+
+        // Read the pending request which can still be answered
+        fn get_open_request(&self, request_id: RequestId) -> Result<AccessRequest> {
+            let request = self.map_access_request
+                .get(request_id)
+                .ok_or(Error::RequestDoesNotExist)?;
+            self.ensure_caller_tenant(&request.tenant)?;
+            if request.expiry < self.env().block_number() {
+                return Err(Error::RequestExpired);
+            }
+            Ok(request)
+        }
+
+        // Approve the request, the Role is added to the User the same way as add_user_or_group_to_role
+        #[ink(message)]
+        pub fn approve_request(&mut self, request_id: RequestId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let request = self.get_open_request(request_id)?;
+            let approver = self.env().caller();
+            self.ensure_role_manager(&approver, &request.role_did)?;
+
+            self.apply_policy_op(self.env().caller(), PolicyOp::AddUserOrGroupToRole(request.user_did, request.role_did))?;
+            self.remove_request(request_id, &request);
+            self.env().emit_event(RequestApproved { request_id, approver });
+            Ok(())
+        }
+
+        // Reject the request
+        #[ink(message)]
+        pub fn reject_request(&mut self, request_id: RequestId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let request = self.get_open_request(request_id)?;
+            let rejecter = self.env().caller();
+            self.ensure_role_manager(&rejecter, &request.role_did)?;
+
+            self.remove_request(request_id, &request);
+            self.env().emit_event(RequestRejected { request_id, rejecter });
+            Ok(())
+        }
+
+        // Remove the expired request, anyone can call it
+        #[ink(message)]
+        pub fn remove_expired_request(&mut self, request_id: RequestId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let request = self.map_access_request
+                .get(request_id)
+                .ok_or(Error::RequestDoesNotExist)?;
+            self.ensure_caller_tenant(&request.tenant)?;
+            if request.expiry >= self.env().block_number() {
+                return Err(Error::RequestNotExpired);
+            }
+
+            self.remove_request(request_id, &request);
+            self.env().emit_event(RequestExpired { request_id });
+            Ok(())
+        }
+
+        // Read the pending request
+        #[ink(message)]
+        pub fn read_request(&self, request_id: RequestId) -> Option<AccessRequest> {
+            self.map_access_request
+                .get(request_id)
+                .filter(|request| request.tenant == self.caller_tenant())
+        }
+
+        // Read the ids of the pending requests for the Role
+        #[ink(message)]
+        pub fn read_role_requests(&self, role_did: RoleDID) -> Vec<RequestId> {
+            self.map_role_requests.get(self.key(&role_did)).unwrap_or_default()
+        }
+
+        // Flag the Role or Permission of the tenant as sensitive, adding it to a Role or adding a Role to it then needs
+        // the approval of threshold of the approvers
+        #[ink(message)]
+        pub fn set_approval_policy(&mut self, tenant: TenantId, did: DIDType, approvers: Vec<AccountId>, threshold: u32) -> Result<()> {
+            self.record_message(selector_bytes!("set_approval_policy"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                if threshold == 0 || threshold as usize > approvers.len() {
+                    return Err(Error::InvalidThreshold);
+                }
+                rbac.map_approval_policy.insert(scoped_key(&tenant, &did), &ApprovalPolicy { approvers, threshold });
+                Ok(())
+            })
+        }
+
+        // Make the Role or Permission of the tenant not sensitive anymore
+        #[ink(message)]
+        pub fn remove_approval_policy(&mut self, tenant: TenantId, did: DIDType) -> Result<()> {
+            self.record_message(selector_bytes!("remove_approval_policy"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.map_approval_policy.remove(scoped_key(&tenant, &did));
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn read_approval_policy(&self, did: DIDType) -> Option<ApprovalPolicy> {
+            self.map_approval_policy.get(self.key(&did))
+        }
+
+        // Sensitive DIDs the operation grants something on, with their ApprovalPolicy.
+        // Joining a Group grants its Roles.
+        fn sensitive_dids(&self, op: &PolicyOp) -> Vec<(DIDType, ApprovalPolicy)> {
+            let dids = match op {
+                PolicyOp::AddUserToGroup(_, group_did) => self.get_role(group_did),
+                PolicyOp::AddUserOrGroupToRole(_, role_did) => vec![*role_did],
+                PolicyOp::AddRoleToPermission(role_did, permission_did) |
+                PolicyOp::AddRoleToPermissionOn(role_did, permission_did, _) => vec![*role_did, *permission_did],
+                _ => vec![],
+            };
+            dids.into_iter()
+                .filter_map(|did| self.map_approval_policy.get(self.key(&did)).map(|policy| (did, policy)))
+                .collect()
+        }
+
+        // Every sensitive DID of the operation has enough approvals from its approvers
+        fn proposal_approved(&self, op: &PolicyOp, approvals: &[AccountId]) -> bool {
+            self.sensitive_dids(op).iter().all(|(_, policy)| {
+                let count = approvals.iter().filter(|account| policy.approvers.contains(account)).count();
+                count >= policy.threshold as usize
+            })
+        }
+
+        // Set how many blocks the new proposals can be approved in
+        #[ink(message)]
+        pub fn set_proposal_lifetime(&mut self, lifetime: BlockNumber) -> Result<()> {
+            self.record_message(selector_bytes!("set_proposal_lifetime"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.proposal_lifetime = lifetime;
+                Ok(())
+            })
+        }
+
+        // Read how many blocks the new proposals can be approved in
+        #[ink(message)]
+        pub fn proposal_lifetime(&self) -> BlockNumber {
+            self.proposal_lifetime
+        }
+
+        // Propose an operation on a sensitive Role or Permission, the proposer approves it if it is an approver
+        #[ink(message)]
+        pub fn propose(&mut self, op: PolicyOp) -> Result<ProposalId> {
+            self.ensure_not_paused()?;
+            let proposer = self.env().caller();
+            self.ensure_admin(&proposer)?;
+            if self.sensitive_dids(&op).is_empty() {
+                return Err(Error::ApprovalNotRequired);
+            }
+
+            let proposal_id = self.next_proposal_id;
+            self.next_proposal_id += 1;
+            self.map_proposal.insert(proposal_id, &Proposal {
+                tenant: self.caller_tenant(),
+                op: op.clone(),
+                proposer,
+                approvals: vec![],
+                expiry: self.env().block_number().saturating_add(self.proposal_lifetime),
+            });
+            self.env().emit_event(ProposalCreated { proposal_id, proposer, op });
+            if self.is_approver(proposal_id, proposer) {
+                self.approve_proposal(proposal_id)?;
+            }
+            Ok(proposal_id)
+        }
+
+        fn is_approver(&self, proposal_id: ProposalId, account: AccountId) -> bool {
+            self.map_proposal.get(proposal_id).map_or(false, |proposal| {
+                proposal.tenant == self.read_account_tenant(account) &&
+                self.sensitive_dids(&proposal.op)
+                    .iter()
+                    .any(|(_, policy)| policy.approvers.contains(&account))
+            })
+        }
+
+        // Approve the proposal, it is executed by the approval reaching the thresholds
+        #[ink(message)]
+        pub fn approve_proposal(&mut self, proposal_id: ProposalId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let mut proposal = self.map_proposal
+                .get(proposal_id)
+                .ok_or(Error::ProposalDoesNotExist)?;
+            if proposal.expiry < self.env().block_number() {
+                return Err(Error::ProposalExpired);
+            }
+            let approver = self.env().caller();
+            if !self.is_approver(proposal_id, approver) {
+                return Err(Error::NotApprover);
+            }
+            if proposal.approvals.contains(&approver) {
+                return Err(Error::ProposalApprovedAlready);
+            }
+
+            proposal.approvals.push(approver);
+            if self.proposal_approved(&proposal.op, &proposal.approvals) {
+                self.execute_policy_op(approver, proposal.op)?;
+                self.map_proposal.remove(proposal_id);
+                self.env().emit_event(ProposalApproved { proposal_id, approver });
+                self.env().emit_event(ProposalExecuted { proposal_id });
+            } else {
+                self.map_proposal.insert(proposal_id, &proposal);
+                self.env().emit_event(ProposalApproved { proposal_id, approver });
+            }
+            Ok(())
+        }
+
+        // Cancel the proposal, the proposer and the owner can cancel it any time, the others after it expires
+        #[ink(message)]
+        pub fn cancel_proposal(&mut self, proposal_id: ProposalId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let proposal = self.map_proposal
+                .get(proposal_id)
+                .ok_or(Error::ProposalDoesNotExist)?;
+            self.ensure_caller_tenant(&proposal.tenant)?;
+            let caller = self.env().caller();
+            if caller != proposal.proposer && caller != self.owner && proposal.expiry >= self.env().block_number() {
+                return Err(Error::NotProposer);
+            }
+
+            self.map_proposal.remove(proposal_id);
+            self.env().emit_event(ProposalCancelled { proposal_id });
+            Ok(())
+        }
+
+        // Read the pending proposal
+        #[ink(message)]
+        pub fn read_proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+            self.map_proposal
+                .get(proposal_id)
+                .filter(|proposal| proposal.tenant == self.caller_tenant())
+        }
+
+        // Allow the User to break the glass for the Permission, the access lasts duration blocks
+        #[ink(message)]
+        pub fn allow_break_glass(&mut self, user_did: UserDID, permission_did: PermissionDID, duration: BlockNumber) -> Result<()> {
+            self.record_message(selector_bytes!("allow_break_glass"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.map_break_glass_duration.insert((rbac.key(&user_did), permission_did), &duration);
+                Ok(())
+            })
+        }
+
+        #[ink(message)]
+        pub fn disallow_break_glass(&mut self, user_did: UserDID, permission_did: PermissionDID) -> Result<()> {
+            self.record_message(selector_bytes!("disallow_break_glass"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                rbac.map_break_glass_duration.remove((rbac.key(&user_did), permission_did));
+                rbac.map_break_glass_access.remove((rbac.key(&user_did), permission_did));
+                Ok(())
+            })
+        }
+
+        // Read how many blocks the break-glass access of the User for the Permission lasts
+        #[ink(message)]
+        pub fn read_break_glass_duration(&self, user_did: UserDID, permission_did: PermissionDID) -> Option<BlockNumber> {
+            self.map_break_glass_duration.get((self.key(&user_did), permission_did))
+        }
+
+        // Get the short-lived access to the Permission for the User the caller is bound to.
+        // The use is recorded until an admin acknowledges it.
+        #[ink(message)]
+        pub fn break_glass(&mut self, permission_did: PermissionDID, reason_hash: [u8; 32]) -> Result<BreakGlassId> {
+            self.ensure_not_paused()?;
+            let account = self.env().caller();
+            let user_did = self.map_account_did
+                .get(account)
+                .ok_or(Error::AccountNotBound)?;
+            let duration = self.map_break_glass_duration
+                .get((self.key(&user_did), permission_did))
+                .ok_or(Error::BreakGlassNotAllowed)?;
+
+            let block_number = self.env().block_number();
+            let expiry = block_number.saturating_add(duration);
+            let break_glass_id = self.next_break_glass_id;
+            self.next_break_glass_id += 1;
+            self.map_break_glass_access.insert((self.key(&user_did), permission_did), &expiry);
+            self.map_break_glass_use.insert(break_glass_id, &BreakGlassUse {
+                tenant: self.caller_tenant(),
+                user_did,
+                permission_did,
+                reason_hash,
+                account,
+                block_number,
+                expiry,
+                acknowledged_by: None,
+            });
+            let tenant = self.caller_tenant();
+            let count = self.read_unacknowledged_break_glass_count();
+            self.map_unacknowledged_break_glass.insert((tenant, count), &break_glass_id);
+            self.map_unacknowledged_index.insert(break_glass_id, &count);
+            self.map_unacknowledged_count.insert(tenant, &(count + 1));
+            self.env().emit_event(BreakGlassUsed {
+                break_glass_id,
+                user_did,
+                permission_did,
+                reason_hash,
+                expiry,
+            });
+            Ok(break_glass_id)
+        }
+
+        fn has_break_glass_access(&self, tenant: &TenantId, user_did: UserDID, permission_did: PermissionDID) -> bool {
+            self.map_break_glass_access
+                .get((scoped_key(tenant, &user_did), permission_did))
+                .map_or(false, |expiry| expiry >= self.env().block_number())
+        }
+
+        // Acknowledge the break-glass use
+        #[ink(message)]
+        pub fn acknowledge_break_glass(&mut self, break_glass_id: BreakGlassId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let admin = self.env().caller();
+            self.ensure_admin(&admin)?;
+            let mut break_glass_use = self.map_break_glass_use
+                .get(break_glass_id)
+                .ok_or(Error::BreakGlassUseDoesNotExist)?;
+            self.ensure_caller_tenant(&break_glass_use.tenant)?;
+            if break_glass_use.acknowledged_by.is_some() {
+                return Err(Error::BreakGlassAcknowledgedAlready);
+            }
+
+            break_glass_use.acknowledged_by = Some(admin);
+            self.map_break_glass_use.insert(break_glass_id, &break_glass_use);
+            // The last unacknowledged use takes the place of the acknowledged one
+            let tenant = break_glass_use.tenant;
+            let last = self.map_unacknowledged_count.get(tenant).unwrap_or_default().saturating_sub(1);
+            let index = self.map_unacknowledged_index.get(break_glass_id).unwrap_or_default();
+            if let Some(last_id) = self.map_unacknowledged_break_glass.get((tenant, last)) {
+                self.map_unacknowledged_break_glass.insert((tenant, index), &last_id);
+                self.map_unacknowledged_index.insert(last_id, &index);
+            }
+            self.map_unacknowledged_break_glass.remove((tenant, last));
+            self.map_unacknowledged_index.remove(break_glass_id);
+            self.map_unacknowledged_count.insert(tenant, &last);
+            self.env().emit_event(BreakGlassAcknowledged { break_glass_id, admin });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn read_break_glass_use(&self, break_glass_id: BreakGlassId) -> Option<BreakGlassUse> {
+            self.map_break_glass_use
+                .get(break_glass_id)
+                .filter(|break_glass_use| break_glass_use.tenant == self.caller_tenant())
+        }
+
+        // Read the number of the break-glass uses of the tenant waiting for an acknowledgement
+        #[ink(message)]
+        pub fn read_unacknowledged_break_glass_count(&self) -> u64 {
+            self.map_unacknowledged_count.get(self.caller_tenant()).unwrap_or_default()
+        }
+
+        // Read at most limit ids of the break-glass uses of the tenant waiting for an acknowledgement, from the index on.
+        // Acknowledging a use moves the last one to its index.
+        #[ink(message)]
+        pub fn read_unacknowledged_break_glass(&self, from: u64, limit: u32) -> Vec<BreakGlassId> {
+            let tenant = self.caller_tenant();
+            let end = self.read_unacknowledged_break_glass_count()
+                .min(from.saturating_add(limit.min(MAX_BREAK_GLASS_PAGE) as u64));
+            (from..end)
+                .filter_map(|index| self.map_unacknowledged_break_glass.get((tenant, index)))
+                .collect()
+        }
+
+        // Set how many blocks the new sessions last.
+        // It applies to every tenant, so only the owner can call it.
+        #[ink(message)]
+        pub fn set_session_lifetime(&mut self, lifetime: BlockNumber) -> Result<()> {
+            self.record_message(selector_bytes!("set_session_lifetime"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                rbac.session_lifetime = lifetime;
+                Ok(())
+            })
+        }
+
+        // Read how many blocks the new sessions last
+        #[ink(message)]
+        pub fn session_lifetime(&self) -> BlockNumber {
+            self.session_lifetime
+        }
+
+        // Open a session without active roles for the User the caller is bound to
+        #[ink(message)]
+        pub fn create_session(&mut self, user_did: UserDID) -> Result<SessionId> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            if self.map_account_did.get(caller) != Some(user_did) {
+                return Err(Error::AccountNotBound);
+            }
+
+            let id = self.next_session_id;
+            self.next_session_id += 1;
+            self.map_session.insert(id, &Session {
+                tenant: self.caller_tenant(),
+                user_did,
+                owner: caller,
+                active_roles: Vec::new(),
+                expiry: self.env().block_number().saturating_add(self.session_lifetime),
+            });
+            Ok(id)
+        }
+
+        // Read the open session of the caller
+        fn get_own_session(&self, session_id: SessionId) -> Result<Session> {
+            let session = self.map_session
+                .get(session_id)
+                .ok_or(Error::SessionDoesNotExist)?;
+            if session.owner != self.env().caller() {
+                return Err(Error::NotSessionOwner);
+            }
+            // The owner moved to another tenant since it opened the session
+            self.ensure_caller_tenant(&session.tenant)?;
+            if session.expiry < self.env().block_number() {
+                return Err(Error::SessionExpired);
+            }
+            Ok(session)
+        }
+
+        // Activate one of the User's roles in the session
+        #[ink(message)]
+        pub fn activate_role(&mut self, session_id: SessionId, role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            let mut session = self.get_own_session(session_id)?;
+            if session.active_roles.contains(&role_did) {
+                return Err(Error::RoleActiveAlready);
+            }
+            if !self.read_user_or_group_roles_in_tenant(&session.tenant, session.user_did).contains(&role_did) {
+                return Err(Error::RoleDoesNotExistForUserOrGroup);
+            }
+            let roles = session.active_roles
                 .iter()
                 .copied()
+                .chain(Some(role_did))
+                .collect::<BTreeSet<_>>();
+            if let Some(id) = self.violated_sod_constraint(SodKind::Dynamic, &roles) {
+                return Err(Error::DsdConstraintViolated(id));
+            }
+
+            session.active_roles.push(role_did);
+            self.map_session.insert(session_id, &session);
+            Ok(())
+        }
+
+        // Deactivate the role in the session
+        #[ink(message)]
+        pub fn deactivate_role(&mut self, session_id: SessionId, role_did: RoleDID) -> Result<()> {
+            self.ensure_not_paused()?;
+            let mut session = self.get_own_session(session_id)?;
+            let index = session.active_roles
+                .iter()
+                .position(|r| *r == role_did)
+                .ok_or(Error::RoleNotActive)?;
+            session.active_roles.remove(index);
+            self.map_session.insert(session_id, &session);
+            Ok(())
+        }
+
+        // Close the session, the expired sessions can be closed too
+        #[ink(message)]
+        pub fn close_session(&mut self, session_id: SessionId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let session = self.map_session
+                .get(session_id)
+                .ok_or(Error::SessionDoesNotExist)?;
+            if session.owner != self.env().caller() {
+                return Err(Error::NotSessionOwner);
+            }
+            self.map_session.remove(session_id);
+            Ok(())
+        }
+
+        // Read the session
+        #[ink(message)]
+        pub fn read_session(&self, session_id: SessionId) -> Option<Session> {
+            self.map_session
+                .get(session_id)
+                .filter(|session| session.tenant == self.caller_tenant())
+        }
+
+        // Check access with the active roles of the session only.
+        // A role removed from the User after its activation does not count.
+        #[ink(message)]
+        pub fn check_session_access(&self, session_id: SessionId, permission_did: PermissionDID) -> bool {
+            let session = match self.map_session.get(session_id) {
+                Some(session) if session.expiry >= self.env().block_number() => session,
+                _ => return false,
+            };
+            self.access_in_tenant(&session.tenant, session.user_did, permission_did, &[], Some(&session.active_roles))
+        }
+
+        // Subscribe the contract to the changes of the Group, Role or Permission
+        #[ink(message)]
+        pub fn subscribe(&mut self, subscriber: AccountId, did: DIDType) -> Result<()> {
+            self.record_message(selector_bytes!("subscribe"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_admin(&rbac.env().caller())?;
+                let mut subscribers = rbac.map_subscribers.get(rbac.key(&did)).unwrap_or_default();
+                if subscribers.contains(&subscriber) {
+                    return Err(Error::SubscriberExistsAlready);
+                }
+                if subscribers.len() >= MAX_SUBSCRIBERS_PER_DID {
+                    return Err(Error::TooManySubscribers);
+                }
+                subscribers.push(subscriber);
+                rbac.map_subscribers.insert(rbac.key(&did), &subscribers);
+                Ok(())
+            })
+        }
+
+        // Unsubscribe the contract, the admins or the subscriber itself can call it
+        #[ink(message)]
+        pub fn unsubscribe(&mut self, subscriber: AccountId, did: DIDType) -> Result<()> {
+            self.record_message(selector_bytes!("unsubscribe"), |rbac| {
+                rbac.ensure_not_paused()?;
+                let caller = rbac.env().caller();
+                if caller != subscriber {
+                    rbac.ensure_admin(&caller)?;
+                }
+                let mut subscribers = rbac.map_subscribers.get(rbac.key(&did)).unwrap_or_default();
+                let index = subscribers
+                    .iter()
+                    .position(|s| *s == subscriber)
+                    .ok_or(Error::SubscriberDoesNotExist)?;
+                subscribers.remove(index);
+                rbac.map_subscribers.insert(rbac.key(&did), &subscribers);
+                Ok(())
+            })
+        }
+
+        // Read the contracts subscribed to the DID
+        #[ink(message)]
+        pub fn read_subscribers(&self, did: DIDType) -> Vec<AccountId> {
+            self.map_subscribers.get(self.key(&did)).unwrap_or_default()
+        }
+
+        // Set the gas limit of every subscriber callback.
+        // It applies to every tenant, so only the owner can call it.
+        #[ink(message)]
+        pub fn set_callback_gas_limit(&mut self, gas_limit: u64) -> Result<()> {
+            self.record_message(selector_bytes!("set_callback_gas_limit"), |rbac| {
+                rbac.ensure_not_paused()?;
+                rbac.ensure_owner(&rbac.env().caller())?;
+                if gas_limit == 0 {
+                    return Err(Error::InvalidGasLimit);
+                }
+                rbac.callback_gas_limit = gas_limit;
+                Ok(())
+            })
+        }
+
+        // Read the gas limit of every subscriber callback
+        #[ink(message)]
+        pub fn callback_gas_limit(&self) -> u64 {
+            self.callback_gas_limit
+        }
+
+        fn notify_subscribers(&self, op: &PolicyOp) {
+            let (from_did, to_did) = match *op {
+                PolicyOp::AddUserToGroup(user_did, group_did) |
+                PolicyOp::RemoveUserFromGroup(user_did, group_did) => (user_did, group_did),
+                PolicyOp::AddUserOrGroupToRole(user_or_group_did, role_did) |
+                PolicyOp::RemoveUserOrGroupFromRole(user_or_group_did, role_did) => (user_or_group_did, role_did),
+                PolicyOp::AddRoleToPermission(role_did, permission_did) |
+                PolicyOp::RemoveRoleFromPermission(role_did, permission_did) |
+                PolicyOp::AddRoleToPermissionOn(role_did, permission_did, _) |
+                PolicyOp::RemoveRoleFromPermissionOn(role_did, permission_did, _) => (role_did, permission_did),
+                PolicyOp::AddChildResource(parent_did, child_did) |
+                PolicyOp::RemoveChildResource(parent_did, child_did) => (parent_did, child_did),
+            };
+            let subscribers = self.read_subscribers(from_did)
+                .into_iter()
+                .chain(self.read_subscribers(to_did))
+                .collect::<BTreeSet<_>>();
+
+            for subscriber in subscribers {
+                self.env().emit_event(SubscriberNotified {
+                    subscriber,
+                    op: op.clone(),
+                    policy_version: self.policy_version(),
+                });
+                // Best effort: the failure of a subscriber is ignored
+                let _ = build_call::<Environment>()
+                    .callee(subscriber)
+                    .gas_limit(self.callback_gas_limit)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ON_POLICY_CHANGED_SELECTOR))
+                            .push_arg(op)
+                            .push_arg(self.policy_version())
+                    )
+                    .returns::<()>()
+                    .fire();
+            }
+        }
+
+        // Call the selector of the callee with the input and the value, and return the callee's output.
+        // The caller's bound UserDID needs the Permission, and the call has to be allowed for the Permission.
+        // The value has to be transferred with this call, so the balance of the contract is never forwarded.
+        // The message does not write the storage, so a callee re-entering this contract keeps its changes.
+        #[ink(message, payable)]
+        pub fn execute_if_permitted(&self, permission_did: PermissionDID, callee: AccountId, selector: [u8; 4], input: Vec<u8>, value: Balance) -> Result<Vec<u8>> {
+            self.ensure_not_paused()?;
+            if self.map_call_permission.get((self.caller_tenant(), callee, selector)) != Some(permission_did) {
+                return Err(Error::CallNotAllowed);
+            }
+            if self.env().transferred_value() != value {
+                return Err(Error::InvalidTransferredValue);
+            }
+            let user_did = self.map_account_did
+                .get(self.env().caller())
+                .ok_or(Error::AccountNotBound)?;
+            if !self.check_access(user_did, permission_did) {
+                return Err(Error::AccessNotGranted);
+            }
+
+            build_call::<Environment>()
+                .callee(callee)
+                // The callee may check the access on this contract again
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .gas_limit(0)
+                .transferred_value(value)
+                .exec_input(ExecutionInput::new(Selector::new(selector)).push_arg(CallInput(&input)))
+                .returns::<ReturnType<CallOutput>>()
+                .fire()
+                .map(|output| output.0)
+                .map_err(|_| Error::CallFailed)
+        }
+    }
+
+    impl RBAC {
+        // Read User/Group Roles
+        #[ink(message)]
+        pub fn read_user_or_group_roles(&self, user_or_group_did: UserGroupDID) ->Vec<RoleDID> {
+            self.read_user_or_group_roles_in_tenant(&self.caller_tenant(), user_or_group_did)
+        }
+
+        // Read Permission for Roles
+        #[ink(message)]
+        pub fn read_permissions(&self, role_did: RoleDID) ->Vec<PermissionDID> {
+            self.read_permissions_in_tenant(&self.caller_tenant(), role_did)
+        }
+
+        #[ink(message)]
+        pub fn check_access(&self, user_did: UserDID, permission_did: PermissionDID) -> bool {
+            self.check_access_in_tenant(self.caller_tenant(), user_did, permission_did)
+        }
+
+        fn read_user_or_group_roles_in_tenant(&self, tenant: &TenantId, user_or_group_did: UserGroupDID) ->Vec<RoleDID> {
+            self.roles_in_tenant(tenant, user_or_group_did, false)
+        }
+
+        // The roles of the User/Group and its Groups, only the ones in their schedule if scheduled_only
+        fn roles_in_tenant(&self, tenant: &TenantId, user_or_group_did: UserGroupDID, scheduled_only: bool) ->Vec<RoleDID> {
+            // The User transferred to another one has none of the transferred links after its grace period
+            let (grace_groups, grace_roles) = match self.map_transfer_grace.get(scoped_key(tenant, &user_or_group_did)) {
+                Some(expiry) if expiry < self.env().block_number() => self.map_transfer_grace_links
+                    .get(scoped_key(tenant, &user_or_group_did))
+                    .unwrap_or_default(),
+                _ => (Vec::new(), Vec::new()),
+            };
+            let get_role = |did: &UserGroupDID| {
+                let mut roles = self.get_role_in_tenant(tenant, did);
+                if scheduled_only {
+                    roles.retain(|role_did| self.schedule_active(
+                        tenant,
+                        self.map_assignment_schedule.get((scoped_key(tenant, did), *role_did))
+                    ));
+                }
+                roles
+            };
+            let mut vec_roles = Vec::new();
+            let mut direct_roles = get_role(&user_or_group_did);
+            direct_roles.retain(|role_did| !grace_roles.contains(role_did));
+            vec_roles.append(&mut direct_roles);
+
+            // The User/Group DID isn't the same, so just try to get the roles
+            self.read_user_belongs_in_tenant(tenant, user_or_group_did)
+                .iter()
+                .filter(|group| !grace_groups.contains(group))
+                .for_each(|group| 
+                    vec_roles.append(&mut get_role(group))
+            );
+
+            self.read_group_belongs_in_tenant(tenant, user_or_group_did)
+                .iter()
+                .for_each(|group| 
+                    vec_roles.append(&mut get_role(group))
+            );
+
+            vec_roles.into_iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
                 .collect()
         }
 
-        // Add User or Group to the Role
-        #[ink(message)]
-        pub fn add_user_or_group_to_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
-            let role = Role{id: role_did};
-            let mut vec_role = self.map_user_group_to_role.get(&user_or_group_did).unwrap_or_else(Vec::new);
-            if vec_role.contains(&role) {
-                return Err(Error::UserOrGroupHasRoleAlready);
+        fn read_permissions_in_tenant(&self, tenant: &TenantId, role_did: RoleDID) ->Vec<PermissionDID> {
+            self.map_role_to_permission.get(scoped_key(tenant, &role_did)).unwrap_or_else(Vec::new)
+                .iter()
+                .map(|x| x.id )
+                .collect()
+        }
+
+        // Check Access of the User in the given tenant, check_access uses the tenant of the caller
+        #[ink(message)]
+        pub fn check_access_in_tenant(&self, tenant: TenantId, user_did: UserDID, permission_did: PermissionDID) -> bool {
+            self.access_in_tenant(&tenant, user_did, permission_did, &[], None)
+        }
+
+        // Check Access of the User, the conditions of the links can compare the values of the context
+        #[ink(message)]
+        pub fn check_access_with_context(&self, user_did: UserDID, permission_did: PermissionDID, context: Vec<(AttrKey, u64)>) -> bool {
+            self.access_in_tenant(&self.caller_tenant(), user_did, permission_did, &context, None)
+        }
+
+        // Only the active_roles of the User count if given, e.g. the roles activated in a session
+        fn access_in_tenant(&self, tenant: &TenantId, user_did: UserDID, permission_did: PermissionDID, context: &[(AttrKey, u64)], active_roles: Option<&[RoleDID]>) -> bool {
+            if self.lockdown && !self.is_lockdown_allowed(*tenant, permission_did) {
+                return false;
+            }
+            self.roles_in_tenant(tenant, user_did, true)
+                .iter()
+                .filter(|role_did| active_roles.map_or(true, |active_roles| active_roles.contains(role_did)))
+                .any(|&role| {
+                    self.read_permissions_in_tenant(tenant, role).contains(&permission_did) &&
+                        self.schedule_active(tenant, self.map_permission_schedule.get((scoped_key(tenant, &role), permission_did))) &&
+                        self.condition_holds(tenant, &user_did, &role, &permission_did, context)
+            }) || self.has_break_glass_access(tenant, user_did, permission_did)
+        }
+    }
+
+    impl AccessControl for RBAC {
+        // Read User/Group Roles
+        #[ink(message)]
+        fn read_user_or_group_roles(&self, user_or_group_did: UserGroupDID) ->Vec<RoleDID> {
+            RBAC::read_user_or_group_roles(self, user_or_group_did)
+        }
+
+        // Read Permission for Roles
+        #[ink(message)]
+        fn read_permissions(&self, role_did: RoleDID) ->Vec<PermissionDID> {
+            RBAC::read_permissions(self, role_did)
+        }
+
+        #[ink(message)]
+        fn check_access(&self, user_did: UserDID, permission_did: PermissionDID) -> bool {
+            RBAC::check_access(self, user_did, permission_did)
+        }
+    }
+ 
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    #[allow(clippy::bool_assert_comparison)]
+    mod tests 
+    {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        /// Imports `ink_lang` so we can use `#[ink::test]`.
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                caller,
+                ink_env::account_id::<ink_env::DefaultEnvironment>(),
+                1000000,
+                0,
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])),
+            );
+        }
+
+        fn default_accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+            ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().unwrap()
+        }
+
+        fn ecdsa_account(secret_key: &secp256k1::SecretKey) -> AccountId {
+            let public_key = secp256k1::PublicKey::from_secret_key(secp256k1::SECP256K1, secret_key);
+            let mut account = [0u8; 32];
+            ink_env::hash_bytes::<ink_env::hash::Blake2x256>(&public_key.serialize(), &mut account);
+            AccountId::from(account)
+        }
+
+        fn sign_op(rbac: &RBAC, secret_key: &secp256k1::SecretKey, op: PolicyOp, nonce: u64, deadline: BlockNumber) -> [u8; 65] {
+            let hash = rbac.signed_op_hash(op, ecdsa_account(secret_key), nonce, deadline);
+            let message = secp256k1::Message::from_slice(&hash).unwrap();
+            let (recovery_id, compact) = secp256k1::SECP256K1
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        #[ink::test]
+        fn add_single_user_to_group_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+
+            let vec_group_in_user = rbac.read_user_belongs([2;32]);
+            assert_eq!(vec_group_in_user.len(), 1);
+        }
+
+        #[ink::test]
+        fn add_single_user_to_groups_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            rbac.add_user_to_group([2;32], [4;32]).unwrap();
+
+
+            assert_eq!(
+                rbac.add_user_to_group([2;32], [3;32]),
+                Ok(())
+            );
+
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+            let vec_users_in_group = rbac.read_user_group([4;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+            let vec_users_in_group = rbac.read_user_group([3;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+
+            let vec_group_in_user = rbac.read_user_belongs([2;32]);
+            assert_eq!(vec_group_in_user.len(), 3);
+        }
+
+        #[ink::test]
+        fn add_users_to_single_group_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([1;32], [3;32]).unwrap();
+            rbac.add_user_to_group([2;32], [3;32]).unwrap();
+
+            let vec_users_in_group = rbac.read_user_group([3;32]);
+            assert_eq!(vec_users_in_group.len(), 2);
+
+            let vec_group_in_user = rbac.read_user_belongs([1;32]);
+            assert_eq!(vec_group_in_user.len(), 1);
+            let vec_group_in_user = rbac.read_user_belongs([2;32]);
+            assert_eq!(vec_group_in_user.len(), 1);
+        }
+
+        #[ink::test]
+        fn add_single_user_to_group_already() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            assert_eq!(
+                rbac.add_user_to_group([2;32], [1;32]),
+                Err(Error::GroupHasUserOrGroupAlready)
+            );
+        }
+
+        #[ink::test]
+        fn remove_users_from_group_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            rbac.add_user_to_group([3;32], [1;32]).unwrap();
+            rbac.add_user_to_group([4;32], [1;32]).unwrap();
+
+            // remove user from group
+            assert_eq!(
+                rbac.remove_user_from_group([4;32], [1;32]),
+                Ok(())
+            );
+
+            // new user count should be reduced 
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 2);
+            let vec_group_in_user = rbac.read_user_belongs([4;32]);
+            assert_eq!(vec_group_in_user.len(), 0);
+        }
+
+        #[ink::test]
+        fn remove_user_from_groups_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
+            rbac.add_user_to_group([1;32], [3;32]).unwrap();
+
+            // remove user from group
+            assert_eq!(
+                rbac.remove_user_from_group([1;32], [2;32]),
+                Ok(())
+            );
+
+            // new user count should be reduced 
+            let vec_users_in_group = rbac.read_user_group([3;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+            assert_eq!(vec_users_in_group[0], [1;32]);
+        }
+
+        #[ink::test]
+        fn remove_user_from_groups_and_added_again() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
+            rbac.add_user_to_group([1;32], [3;32]).unwrap();
+            rbac.remove_user_from_group([1;32], [2;32]).unwrap();
+
+            assert_eq!(
+                rbac.add_user_to_group([1;32], [2;32]),
+                Ok(())
+            );
+
+            let vec_users_in_group = rbac.read_user_group([2;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+            assert_eq!(vec_users_in_group[0], [1;32]);
+            let vec_users_in_group = rbac.read_user_group([3;32]);
+            assert_eq!(vec_users_in_group.len(), 1);
+            assert_eq!(vec_users_in_group[0], [1;32]);
+        }
+
+        //remove_user_from_group_wrong_group() when wrong group id passed
+        #[ink::test]
+        fn remove_user_from_empty_group_fail() {
+            let mut rbac = RBAC::default();
+
+            assert_eq!(
+                rbac.remove_user_from_group([2;32], [3;32]),
+                Err(Error::GroupDoesNotExist)
+            );
+        }
+
+        //remove_user_from_group_wrong_group() when wrong group id passed
+        #[ink::test]
+        fn remove_user_from_group_wrong_group_fail() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            rbac.add_user_to_group([3;32], [1;32]).unwrap();
+
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 2);
+
+            // remove user from group where group id is wrong
+            assert_eq!(
+                rbac.remove_user_from_group([2;32], [4;32]),
+                Err(Error::GroupDoesNotExist)
+            );
+        }
+
+
+        //user's and group's DID are the same
+        #[ink::test]
+        fn user_group_did_are_same() {
+            let mut rbac = RBAC::default();
+            assert_eq!(rbac.add_user_to_group([1;32], [1;32]),
+                Err(Error::UserGroupAreSame)
+            );
+        }
+
+        //user's and group's DID are the same
+        #[ink::test]
+        fn user_group_did_conflict() {
+            let mut rbac = RBAC::default();
+            let (user_did, group_did) = ([2;32], [1;32]);
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+
+            assert_eq!(rbac.add_user_to_group(group_did, [3;32]),
+                Err(Error::UserGroupAreSame)
+            );
+
+            assert_eq!(rbac.add_user_to_group([3;32], user_did),
+                Err(Error::UserGroupAreSame)
+            );
+        }
+
+        //remove_user_from_group_wrong_user() when wrong user id passed
+        #[ink::test]
+        fn remove_user_from_group_wrong_user() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            rbac.add_user_to_group([3;32], [1;32]).unwrap();
+
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 2);
+
+            // remove user from group where group id is wrong
+            assert_eq!(
+                rbac.remove_user_from_group([4;32], [1;32]),
+                Err(Error::UserOrGroupDoesNotExistInGroup)
+            );
+        }
+
+        #[ink::test]
+        fn read_user_group_empty_works() {
+            let rbac = RBAC::default();
+            
+            // pass wrong group id
+            let vec_users_in_group = rbac.read_user_group([1;32]);
+            assert_eq!(vec_users_in_group.len(), 0);
+            let vec_group_in_user = rbac.read_user_belongs([1;32]);
+            assert_eq!(vec_group_in_user.len(), 0);
+        }
+
+        // Assign single roles to single user/group: one to one
+        #[ink::test]
+        fn assign_single_role_to_user_or_group() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+
+            let vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 1);
+        }
+
+        #[ink::test]
+        fn assign_single_role_to_user_already() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            assert_eq!(
+                rbac.add_user_or_group_to_role([1;32], [10;32]),
+                Err(Error::UserOrGroupHasRoleAlready),
+            );
+        }
+
+        // Assign muliple roles to single user/group: many to one
+        #[ink::test]
+        fn assign_roles_to_user_or_group() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
+
+            let vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 3);
+        }
+
+        #[ink::test]
+        fn assign_role_to_users_or_groups() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([3;32], [10;32]).unwrap();
+
+            let vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 1);
+            let vec_roles = rbac.read_user_or_group_roles([2;32]);
+            assert_eq!(vec_roles.len(), 1);
+            let vec_roles = rbac.read_user_or_group_roles([3;32]);
+            assert_eq!(vec_roles.len(), 1);
+        }
+
+        #[ink::test]
+        fn assign_multiple_role_to_user_with_group() {
+            let mut rbac = RBAC::default();
+            let user_did = [1;32];
+            let group_did = [2;32];
+            let user_role_did = [10;32];
+            let group_role_did = [11;32];
+            rbac.add_user_or_group_to_role(user_did, user_role_did).unwrap();
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, group_role_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, user_role_did).unwrap();
+
+            let vec_roles = rbac.read_user_or_group_roles(user_did);
+            assert_eq!(vec_roles.len(), 2);
+        }
+
+        #[ink::test]
+        fn remove_role_from_single_user() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+
+            // remove user/group from role
+            assert_eq!(
+                rbac.remove_user_or_group_from_role([1;32], [10;32]),
+                Ok(())
+            );
+
+            // new user count should be reduced 
+            let vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 0)
+        }
+
+        #[ink::test]
+        fn remove_role_from_user_or_group_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
+
+            let mut vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 3);
+
+            // remove user/group from role
+            assert_eq!(
+                rbac.remove_user_or_group_from_role([1;32], [12;32]),
+                Ok(())
+            );
+
+            // new user count should be reduced 
+            vec_roles = rbac.read_user_or_group_roles([1;32]);
+            assert_eq!(vec_roles.len(), 2)
+        }
+
+         //remove_user_or_group_from_role_wrong_user_group() when wrong user/group id passed
+         #[ink::test]
+         fn remove_user_or_group_from_role_wrong_user_group() {
+             let mut rbac = RBAC::default();
+             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+             rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+ 
+             // delete user/group from role where group id is wrong
+             assert_eq!(
+                 rbac.remove_user_or_group_from_role([2;32], [10;32]),
+                 Err(Error::UserOrGroupDoesNotExist)
+             );
+         }
+ 
+         //remove_user_or_group_from_role_wrong_role() when wrong role id passed
+         #[ink::test]
+         fn remove_user_or_group_from_role_wrong_role() {
+             let mut rbac = RBAC::default();
+             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+             rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+ 
+             // remove user/group from role where role id is wrong
+             assert_eq!(
+                 rbac.remove_user_or_group_from_role([1;32], [12;32]),
+                 Err(Error::RoleDoesNotExistForUserOrGroup)
+             );
+         }
+ 
+         // Assign single permission to single role: one to one
+        #[ink::test]
+        fn assign_single_permission_to_role() {
+            let mut rbac = RBAC::default();
+            let perm_1 = [20;32];
+            let role_1 = [10;32];
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 1);
+        }
+
+        #[ink::test]
+        fn assign_single_permission_to_role_multiple_times() {
+            let mut rbac = RBAC::default();
+            let perm_1 = [20;32];
+            let role_1 = [10;32];
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            assert_eq!(
+                rbac.add_role_to_permission(role_1, perm_1),
+                Err(Error::RoleHasPermissionAlready)
+            );
+        }
+
+        // Assign muliple permission to single role: many to one
+        #[ink::test]
+        fn assign_multiple_permission_to_role() {
+            let mut rbac = RBAC::default();
+            let role_1 = [10;32];
+            let (perm_1, perm_2, perm_3) = ([20;32], [21;32], [22;32]);
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            rbac.add_role_to_permission(role_1, perm_2).unwrap();
+            rbac.add_role_to_permission(role_1, perm_3).unwrap();
+
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 3);
+        }
+
+        // Assign muliple permission to muliple role: many to many
+        #[ink::test]
+        fn assign_single_permission_to_multiple_role() {
+            let mut rbac = RBAC::default();
+            let (role_1, role_2) = ([10;32], [11;32]);
+            let perm_1 = [20;32];
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            rbac.add_role_to_permission(role_2, perm_1).unwrap();
+
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 1);
+
+            let vec_roles = rbac.read_permissions(role_2);
+            assert_eq!(vec_roles.len(), 1);
+        }
+
+        #[ink::test]
+        fn remove_signle_role_from_permission_works() {
+            let mut rbac = RBAC::default();
+            let role_1 = [10;32];
+            let perm_1 = [20;32];
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+
+            // delete role from permission
+            assert_eq!(
+                rbac.remove_role_from_permission(role_1, perm_1),
+                Ok(())
+            );
+
+            // new permission count should be reduced 
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 0)
+        }
+
+        #[ink::test]
+        fn remove_non_exist_permission_fail() {
+            let mut rbac = RBAC::default();
+
+            assert_eq!(
+                rbac.remove_role_from_permission([10;32], [20;32]),
+                Err(Error::RoleDoesNotExist)
+            );
+        }
+
+        #[ink::test]
+        fn remove_role_from_permission_works() {
+            let mut rbac = RBAC::default();
+            let role_1 = [10;32];
+            let (perm_1, perm_2, perm_3) = ([20;32], [21;32], [22;32]);
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            rbac.add_role_to_permission(role_1, perm_2).unwrap();
+            rbac.add_role_to_permission(role_1, perm_3).unwrap();
+
+            // delete role from permission
+            assert_eq!(
+                rbac.remove_role_from_permission(role_1, perm_3),
+                Ok(())
+            );
+
+            // new permission count should be reduced 
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 2)
+        }
+
+        //remove_remove_role_from_permission_wrong_role() when wrong role id passed
+        #[ink::test]
+        fn remove_role_from_permission_wrong_role() {
+            let (perm_1, perm_2) = ([20;32], [21;32]);
+            let (role_1, role_not_exist) = ([10;32], [13;32]);
+            let mut rbac = RBAC::default();
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            rbac.add_role_to_permission(role_1, perm_2).unwrap();
+ 
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 2);
+ 
+            // delete role from permission where role id is wrong
+            assert_eq!(
+                rbac.remove_role_from_permission(role_not_exist, perm_1),
+                Err(Error::RoleDoesNotExist)
+            );
+        }
+ 
+        //remove_role_from_permission_wrong_permission() when wrong permission id passed
+        #[ink::test]
+        fn remove_role_from_permission_wrong_permission() {
+            let mut rbac = RBAC::default();
+            let role_1 = [10;32];
+            let (perm_1, perm_2, perm_not_exist) = ([20;32], [21;32], [22;32]);
+
+            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            rbac.add_role_to_permission(role_1, perm_2).unwrap();
+ 
+            let vec_roles = rbac.read_permissions(role_1);
+            assert_eq!(vec_roles.len(), 2);
+ 
+            // delete role from permission where permission id is wrong
+            assert_eq!(
+                rbac.remove_role_from_permission(role_1, perm_not_exist),
+                Err(Error::PermissionNotExistInRole)
+            );
+        }
+ 
+        #[ink::test]
+        fn read_single_user_perm_empty() {
+            let rbac = RBAC::default();
+
+            assert_eq!(rbac.check_access([1;32], [11;32]), false);
+        }
+
+
+        #[ink::test]
+        fn read_single_user_permission() {
+            let mut rbac = RBAC::default();
+            let user_did = [1;32];
+            let user_role_did = [10;32];
+            let user_permission_did = [11;32];
+
+            assert_eq!(rbac.check_access(user_did, user_permission_did), false);
+            rbac.add_user_or_group_to_role(user_did, user_role_did).unwrap();
+            rbac.add_role_to_permission(user_role_did, user_permission_did).unwrap();
+            assert_eq!(rbac.check_access(user_did, user_permission_did), true);
+        }
+
+        #[ink::test]
+        fn read_user_in_group_permission() {
+            let mut rbac = RBAC::default();
+            let user_did = [1;32];
+            let group_did = [2;32];
+            let group_role_did = [10;32];
+            let group_permission_did = [11;32];
+
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, group_role_did).unwrap();
+            rbac.add_role_to_permission(group_role_did, group_permission_did).unwrap();
+            assert_eq!(rbac.check_access(user_did, group_permission_did), true);
+        }
+
+        #[ink::test]
+        fn access_control_trait_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [11;32]).unwrap();
+            assert!(<RBAC as AccessControl>::check_access(&rbac, [1;32], [11;32]));
+            assert_eq!(<RBAC as AccessControl>::read_user_or_group_roles(&rbac, [1;32]), vec![[10;32]]);
+            assert_eq!(<RBAC as AccessControl>::read_permissions(&rbac, [10;32]), vec![[11;32]]);
+        }
+
+        #[ink::test]
+        fn issue_and_verify_token_works() {
+            let mut rbac = RBAC::default();
+            let (user_did, role_did, permission_did) = ([1;32], [10;32], [11;32]);
+            rbac.add_user_or_group_to_role(user_did, role_did).unwrap();
+            rbac.add_role_to_permission(role_did, permission_did).unwrap();
+
+            let token = rbac.issue_token(user_did, permission_did, 1).unwrap();
+            assert_eq!(token.policy_version, rbac.policy_version());
+            assert!(rbac.verify_token(token.clone()));
+
+            // The nonce makes every token unique
+            let other_token = rbac.issue_token(user_did, permission_did, 1).unwrap();
+            assert_ne!(token.hash(), other_token.hash());
+
+            // A modified token was never issued
+            let mut forged = token.clone();
+            forged.expiry = 100;
+            assert!(!rbac.verify_token(forged));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(!rbac.verify_token(token));
+        }
+
+        #[ink::test]
+        fn issue_token_without_access_fail() {
+            let mut rbac = RBAC::default();
+            assert_eq!(
+                rbac.issue_token([1;32], [11;32], 1),
+                Err(Error::AccessNotGranted)
+            );
+        }
+
+        #[ink::test]
+        fn issue_token_by_other_account_fail() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [11;32]).unwrap();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+
+            set_caller(accounts.eve);
+            assert_eq!(rbac.issue_token([1;32], [11;32], 1), Err(Error::NotConsumer));
+            set_caller(accounts.bob);
+            let token = rbac.issue_token([1;32], [11;32], 1).unwrap();
+            assert_eq!(rbac.read_issued_tokens(DEFAULT_TENANT, 0, 10), vec![token.hash()]);
+            assert_eq!(rbac.read_issued_token_count(DEFAULT_TENANT), 1);
+        }
+
+        #[ink::test]
+        fn verify_token_after_permission_removed_fail() {
+            let mut rbac = RBAC::default();
+            let (user_did, role_did, permission_did) = ([1;32], [10;32], [11;32]);
+            rbac.add_user_or_group_to_role(user_did, role_did).unwrap();
+            rbac.add_role_to_permission(role_did, permission_did).unwrap();
+            let token = rbac.issue_token(user_did, permission_did, 10).unwrap();
+
+            rbac.remove_role_from_permission(role_did, permission_did).unwrap();
+            assert!(rbac.policy_version() > token.policy_version);
+            assert!(!rbac.verify_token(token));
+        }
+
+        #[ink::test]
+        fn revoke_token_works() {
+            let mut rbac = RBAC::default();
+            let (user_did, role_did, permission_did) = ([1;32], [10;32], [11;32]);
+            rbac.add_user_or_group_to_role(user_did, role_did).unwrap();
+            rbac.add_role_to_permission(role_did, permission_did).unwrap();
+            let token = rbac.issue_token(user_did, permission_did, 10).unwrap();
+
+            assert_eq!(rbac.revoke_token(token.clone()), Ok(()));
+            assert!(!rbac.verify_token(token.clone()));
+            assert_eq!(rbac.read_revoked_tokens(DEFAULT_TENANT, 0, 10), vec![token.hash()]);
+            assert_eq!(rbac.read_revoked_token_count(DEFAULT_TENANT), 1);
+            assert_eq!(
+                rbac.revoke_token(token),
+                Err(Error::TokenRevokedAlready)
+            );
+        }
+
+        #[ink::test]
+        fn non_admin_change_policy_fail() {
+            let mut rbac = RBAC::default();
+            set_caller(default_accounts().bob);
+            assert_eq!(rbac.add_user_to_group([2;32], [1;32]), Err(Error::NotAdmin));
+            assert_eq!(rbac.add_user_or_group_to_role([1;32], [10;32]), Err(Error::NotAdmin));
+            assert_eq!(rbac.add_role_to_permission([10;32], [20;32]), Err(Error::NotAdmin));
+        }
+
+        #[ink::test]
+        fn add_admin_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            assert!(rbac.is_admin(accounts.alice));
+            assert!(!rbac.is_admin(accounts.bob));
+
+            rbac.add_admin(accounts.bob).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(rbac.add_user_to_group([2;32], [1;32]), Ok(()));
+
+            // Admins cannot manage the other admins
+            assert_eq!(rbac.add_admin(accounts.charlie), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn remove_admin_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.remove_admin(accounts.bob).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.add_user_to_group([2;32], [1;32]), Err(Error::NotAdmin));
+        }
+
+        #[ink::test]
+        fn execute_signed_works() {
+            let mut rbac = RBAC::default();
+            let secret_key = secp256k1::SecretKey::from_slice(&[7;32]).unwrap();
+            let signer = ecdsa_account(&secret_key);
+            rbac.add_admin(signer).unwrap();
+
+            // The transaction is sent by someone else than the signer
+            set_caller(default_accounts().bob);
+            let op = PolicyOp::AddUserToGroup([2;32], [1;32]);
+            let signature = sign_op(&rbac, &secret_key, op.clone(), 0, 10);
+            assert_eq!(rbac.execute_signed(op.clone(), signer, 0, 10, signature), Ok(()));
+            assert_eq!(rbac.read_user_group([1;32]), vec![[2;32]]);
+            assert_eq!(rbac.read_signer_nonce(signer), 1);
+
+            // Replay the same signed operation
+            assert_eq!(
+                rbac.execute_signed(op, signer, 0, 10, signature),
+                Err(Error::InvalidNonce)
+            );
+        }
+
+        #[ink::test]
+        fn execute_signed_in_signer_tenant_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            let secret_key = secp256k1::SecretKey::from_slice(&[7;32]).unwrap();
+            let signer = ecdsa_account(&secret_key);
+            rbac.set_account_tenant(signer, [7;32]).unwrap();
+            rbac.set_account_tenant(accounts.charlie, [7;32]).unwrap();
+            rbac.add_admin(signer).unwrap();
+
+            // The relayer is in the default tenant, the operation changes the tenant of the signer
+            set_caller(accounts.bob);
+            let op = PolicyOp::AddUserToGroup([2;32], [1;32]);
+            let signature = sign_op(&rbac, &secret_key, op.clone(), 0, 10);
+            assert_eq!(rbac.execute_signed(op, signer, 0, 10, signature), Ok(()));
+            assert!(rbac.read_user_group([1;32]).is_empty());
+
+            set_caller(accounts.charlie);
+            assert_eq!(rbac.read_user_group([1;32]), vec![[2;32]]);
+        }
+
+        #[ink::test]
+        fn execute_signed_not_admin_fail() {
+            let mut rbac = RBAC::default();
+            let secret_key = secp256k1::SecretKey::from_slice(&[7;32]).unwrap();
+            let signer = ecdsa_account(&secret_key);
+
+            let op = PolicyOp::AddUserToGroup([2;32], [1;32]);
+            let signature = sign_op(&rbac, &secret_key, op.clone(), 0, 10);
+            assert_eq!(
+                rbac.execute_signed(op, signer, 0, 10, signature),
+                Err(Error::NotAdmin)
+            );
+        }
+
+        #[ink::test]
+        fn execute_signed_invalid_signature_fail() {
+            let mut rbac = RBAC::default();
+            let secret_key = secp256k1::SecretKey::from_slice(&[7;32]).unwrap();
+            let signer = ecdsa_account(&secret_key);
+            rbac.add_admin(signer).unwrap();
+
+            // The signed operation is changed
+            let signature = sign_op(&rbac, &secret_key, PolicyOp::AddUserToGroup([2;32], [1;32]), 0, 10);
+            assert_eq!(
+                rbac.execute_signed(PolicyOp::AddUserToGroup([3;32], [1;32]), signer, 0, 10, signature),
+                Err(Error::InvalidSignature)
+            );
+
+            // The operation is signed by another key
+            let other_key = secp256k1::SecretKey::from_slice(&[8;32]).unwrap();
+            let op = PolicyOp::AddUserToGroup([2;32], [1;32]);
+            let signature = sign_op(&rbac, &other_key, op.clone(), 0, 10);
+            assert_eq!(
+                rbac.execute_signed(op, signer, 0, 10, signature),
+                Err(Error::InvalidSignature)
+            );
+        }
+
+        #[ink::test]
+        fn execute_signed_expired_fail() {
+            let mut rbac = RBAC::default();
+            let secret_key = secp256k1::SecretKey::from_slice(&[7;32]).unwrap();
+            let signer = ecdsa_account(&secret_key);
+            rbac.add_admin(signer).unwrap();
+
+            let op = PolicyOp::AddUserToGroup([2;32], [1;32]);
+            let signature = sign_op(&rbac, &secret_key, op.clone(), 0, 0);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert_eq!(
+                rbac.execute_signed(op, signer, 0, 0, signature),
+                Err(Error::SignatureExpired)
+            );
+        }
+
+        #[ink::test]
+        fn bind_account_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            assert_eq!(rbac.read_account_did(accounts.bob), None);
+
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            assert_eq!(rbac.read_account_did(accounts.bob), Some([1;32]));
+
+            rbac.unbind_account(accounts.bob).unwrap();
+            assert_eq!(rbac.read_account_did(accounts.bob), None);
+            assert_eq!(rbac.unbind_account(accounts.bob), Err(Error::AccountNotBound));
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.bind_account(accounts.bob, [1;32]), Err(Error::NotAdmin));
+        }
+
+        #[ink::test]
+        fn allow_call_works() {
+            let mut rbac = RBAC::default();
+            let callee = default_accounts().django;
+            rbac.allow_call([11;32], callee, [1, 2, 3, 4]).unwrap();
+            assert_eq!(rbac.read_call_permission(callee, [1, 2, 3, 4]), Some([11;32]));
+            assert_eq!(rbac.read_call_permission(callee, [4, 3, 2, 1]), None);
+
+            rbac.disallow_call(callee, [1, 2, 3, 4]).unwrap();
+            assert_eq!(rbac.read_call_permission(callee, [1, 2, 3, 4]), None);
+            assert_eq!(rbac.disallow_call(callee, [1, 2, 3, 4]), Err(Error::CallNotAllowed));
+        }
+
+        #[ink::test]
+        fn execute_if_permitted_not_allowed_call_fail() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.allow_call([11;32], accounts.django, [1, 2, 3, 4]).unwrap();
+
+            // The call is allowed for another Permission
+            assert_eq!(
+                rbac.execute_if_permitted([12;32], accounts.django, [1, 2, 3, 4], Vec::new(), 0),
+                Err(Error::CallNotAllowed)
+            );
+            assert_eq!(
+                rbac.execute_if_permitted([11;32], accounts.django, [4, 3, 2, 1], Vec::new(), 0),
+                Err(Error::CallNotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn execute_if_permitted_without_access_fail() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.allow_call([11;32], accounts.django, [1, 2, 3, 4]).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                rbac.execute_if_permitted([11;32], accounts.django, [1, 2, 3, 4], Vec::new(), 0),
+                Err(Error::AccountNotBound)
+            );
+
+            set_caller(accounts.alice);
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(
+                rbac.execute_if_permitted([11;32], accounts.django, [1, 2, 3, 4], Vec::new(), 0),
+                Err(Error::AccessNotGranted)
+            );
+        }
+
+        // The checks pass and the call is forwarded, which the off-chain environment cannot execute
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract evaluation")]
+        fn execute_if_permitted_forwards_call() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.allow_call([11;32], accounts.django, [1, 2, 3, 4]).unwrap();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [11;32]).unwrap();
+
+            set_caller(accounts.bob);
+            let _ = rbac.execute_if_permitted([11;32], accounts.django, [1, 2, 3, 4], vec![1, 2], 0);
+        }
+
+        #[ink::test]
+        fn subscribe_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.subscribe(accounts.django, [10;32]).unwrap();
+            rbac.subscribe(accounts.eve, [10;32]).unwrap();
+            assert_eq!(rbac.read_subscribers([10;32]), vec![accounts.django, accounts.eve]);
+            assert_eq!(
+                rbac.subscribe(accounts.django, [10;32]),
+                Err(Error::SubscriberExistsAlready)
+            );
+
+            // The subscriber can unsubscribe itself, but not the other subscribers
+            set_caller(accounts.django);
+            assert_eq!(rbac.unsubscribe(accounts.eve, [10;32]), Err(Error::NotAdmin));
+            assert_eq!(rbac.unsubscribe(accounts.django, [10;32]), Ok(()));
+            assert_eq!(rbac.read_subscribers([10;32]), vec![accounts.eve]);
+            assert_eq!(
+                rbac.unsubscribe(accounts.django, [10;32]),
+                Err(Error::SubscriberDoesNotExist)
+            );
+        }
+
+        #[ink::test]
+        fn notify_subscribers_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.subscribe(accounts.django, [10;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [20;32]).unwrap();
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+
+            // The off-chain environment cannot call the subscriber, the event is emitted before the call
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                rbac.add_user_or_group_to_role([1;32], [10;32])
+            }));
+            assert!(result.is_err());
+            let events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(events.len(), 1);
+            type Event = <RBAC as ink_lang::reflect::ContractEventBase>::Type;
+            match <Event as scale::Decode>::decode(&mut &events[0].data[..]).unwrap() {
+                Event::SubscriberNotified(event) => {
+                    assert_eq!(event.subscriber, accounts.django);
+                    assert_eq!(event.op, PolicyOp::AddUserOrGroupToRole([1;32], [10;32]));
+                    assert_eq!(event.policy_version, rbac.policy_version());
+                }
+                _ => panic!("unexpected event"),
             }
-            vec_role.push(role);
-            self.map_user_group_to_role.insert(user_or_group_did, &vec_role);
-            Ok(())
         }
 
-        // Remove User or Group from the Role
-        #[ink(message)]
-        pub fn remove_user_or_group_from_role(&mut self, user_or_group_did: UserGroupDID, role_did: RoleDID) -> Result<()> {
-            let mut roles = self.map_user_group_to_role
-                .get(&user_or_group_did)
-                .ok_or(Error::UserOrGroupDoesNotExist)?;
-            let index = roles
-                .iter()
-                .position(|r| r.id == role_did)
-                .ok_or(Error::RoleDoesNotExistForUserOrGroup)?;
-            roles.remove(index);
-            self.map_user_group_to_role.insert(&user_or_group_did, &roles);
-            Ok(())
+        #[ink::test]
+        fn subscribe_too_many_fail() {
+            let mut rbac = RBAC::default();
+            for i in 0..MAX_SUBSCRIBERS_PER_DID {
+                rbac.subscribe(AccountId::from([i as u8; 32]), [10;32]).unwrap();
+            }
+            assert_eq!(
+                rbac.subscribe(AccountId::from([0xff; 32]), [10;32]),
+                Err(Error::TooManySubscribers)
+            );
         }
 
-        fn get_role(&self, user_or_group_did: &UserGroupDID) -> Vec<RoleDID>{
-            self.map_user_group_to_role.get(user_or_group_did).unwrap_or_else(Vec::new)
-                .iter()
-                .map(|role| role.id)
-                .collect()
+        #[ink::test]
+        fn set_callback_gas_limit_works() {
+            let mut rbac = RBAC::default();
+            assert_eq!(rbac.callback_gas_limit(), DEFAULT_CALLBACK_GAS_LIMIT);
+            rbac.set_callback_gas_limit(1000).unwrap();
+            assert_eq!(rbac.callback_gas_limit(), 1000);
+            assert_eq!(rbac.set_callback_gas_limit(0), Err(Error::InvalidGasLimit));
+        }
+
+        #[ink::test]
+        fn ssd_constraint_direct_roles_fail() {
+            let mut rbac = RBAC::default();
+            let (approver, requester) = ([10;32], [11;32]);
+            let id = rbac.add_ssd_constraint(vec![approver, requester], 2).unwrap();
+
+            rbac.add_user_or_group_to_role([1;32], approver).unwrap();
+            assert_eq!(
+                rbac.add_user_or_group_to_role([1;32], requester),
+                Err(Error::SsdConstraintViolated(id))
+            );
+            // Another user can still hold the other role
+            assert_eq!(rbac.add_user_or_group_to_role([2;32], requester), Ok(()));
+        }
+
+        #[ink::test]
+        fn ssd_constraint_group_roles_fail() {
+            let mut rbac = RBAC::default();
+            let (user_did, other_user_did, group_did) = ([1;32], [2;32], [3;32]);
+            let (approver, requester) = ([10;32], [11;32]);
+            let id = rbac.add_ssd_constraint(vec![approver, requester], 2).unwrap();
+
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, approver).unwrap();
+
+            // The User has the approver Role through its Group
+            assert_eq!(
+                rbac.add_user_or_group_to_role(user_did, requester),
+                Err(Error::SsdConstraintViolated(id))
+            );
+
+            // The Group gives the approver Role to a requester
+            rbac.add_user_or_group_to_role(other_user_did, requester).unwrap();
+            assert_eq!(
+                rbac.add_user_to_group(other_user_did, group_did),
+                Err(Error::SsdConstraintViolated(id))
+            );
+            assert_eq!(rbac.read_user_group(group_did), vec![user_did]);
+        }
+
+        #[ink::test]
+        fn ssd_constraint_group_members_fail() {
+            let mut rbac = RBAC::default();
+            let (user_did, group_did) = ([1;32], [3;32]);
+            let (approver, requester) = ([10;32], [11;32]);
+            let id = rbac.add_ssd_constraint(vec![approver, requester], 2).unwrap();
+
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role(user_did, requester).unwrap();
+            assert_eq!(
+                rbac.add_user_or_group_to_role(group_did, approver),
+                Err(Error::SsdConstraintViolated(id))
+            );
+        }
+
+        #[ink::test]
+        fn ssd_constraint_cardinality_works() {
+            let mut rbac = RBAC::default();
+            let roles = vec![[10;32], [11;32], [12;32]];
+            let id = rbac.add_ssd_constraint(roles.clone(), 3).unwrap();
+
+            rbac.add_user_or_group_to_role([1;32], roles[0]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], roles[1]).unwrap();
+            assert_eq!(
+                rbac.add_user_or_group_to_role([1;32], roles[2]),
+                Err(Error::SsdConstraintViolated(id))
+            );
+
+            rbac.remove_ssd_constraint(id).unwrap();
+            assert_eq!(rbac.read_ssd_constraint(id), None);
+            assert_eq!(rbac.read_role_ssd_constraints(roles[2]), Vec::<ConstraintId>::new());
+            assert_eq!(rbac.add_user_or_group_to_role([1;32], roles[2]), Ok(()));
+        }
+
+        #[ink::test]
+        fn add_invalid_ssd_constraint_fail() {
+            let mut rbac = RBAC::default();
+            assert_eq!(
+                rbac.add_ssd_constraint(vec![[10;32], [10;32]], 2),
+                Err(Error::InvalidSsdConstraint)
+            );
+            assert_eq!(
+                rbac.add_ssd_constraint(vec![[10;32], [11;32]], 3),
+                Err(Error::InvalidSsdConstraint)
+            );
+            assert_eq!(
+                rbac.remove_ssd_constraint(0),
+                Err(Error::SsdConstraintDoesNotExist)
+            );
+        }
+
+        #[ink::test]
+        fn add_ssd_constraint_violated_already_fail() {
+            let mut rbac = RBAC::default();
+            let (user_did, group_did) = ([1;32], [3;32]);
+            let (approver, requester) = ([10;32], [11;32]);
+            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, approver).unwrap();
+            rbac.add_user_or_group_to_role(user_did, requester).unwrap();
+            assert_eq!(
+                rbac.add_ssd_constraint(vec![approver, requester], 2),
+                Err(Error::SsdConstraintViolatedAlready)
+            );
+
+            // The constraint works once the conflicting assignment is gone
+            rbac.remove_user_or_group_from_role(user_did, requester).unwrap();
+            let id = rbac.add_ssd_constraint(vec![approver, requester], 2).unwrap();
+            assert_eq!(
+                rbac.add_user_or_group_to_role(user_did, requester),
+                Err(Error::SsdConstraintViolated(id))
+            );
+        }
+
+        fn setup_session_roles(rbac: &mut RBAC) -> SessionId {
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
+
+            set_caller(accounts.bob);
+            rbac.create_session([1;32]).unwrap()
+        }
+
+        #[ink::test]
+        fn check_session_access_works() {
+            let mut rbac = RBAC::default();
+            let session_id = setup_session_roles(&mut rbac);
+            assert!(!rbac.check_session_access(session_id, [20;32]));
+
+            rbac.activate_role(session_id, [10;32]).unwrap();
+            assert!(rbac.check_session_access(session_id, [20;32]));
+            assert!(!rbac.check_session_access(session_id, [21;32]));
+            // check_access still uses all the roles
+            assert!(rbac.check_access([1;32], [21;32]));
+
+            rbac.deactivate_role(session_id, [10;32]).unwrap();
+            assert!(!rbac.check_session_access(session_id, [20;32]));
+            assert_eq!(rbac.deactivate_role(session_id, [10;32]), Err(Error::RoleNotActive));
+
+            rbac.close_session(session_id).unwrap();
+            assert_eq!(rbac.read_session(session_id), None);
+        }
+
+        #[ink::test]
+        fn activate_role_dsd_constraint_fail() {
+            let mut rbac = RBAC::default();
+            let id = rbac.add_dsd_constraint(vec![[10;32], [11;32]], 2).unwrap();
+            let session_id = setup_session_roles(&mut rbac);
+
+            rbac.activate_role(session_id, [10;32]).unwrap();
+            assert_eq!(
+                rbac.activate_role(session_id, [11;32]),
+                Err(Error::DsdConstraintViolated(id))
+            );
+            rbac.deactivate_role(session_id, [10;32]).unwrap();
+            assert_eq!(rbac.activate_role(session_id, [11;32]), Ok(()));
+        }
+
+        #[ink::test]
+        fn activate_role_not_assigned_fail() {
+            let mut rbac = RBAC::default();
+            let session_id = setup_session_roles(&mut rbac);
+            assert_eq!(
+                rbac.activate_role(session_id, [12;32]),
+                Err(Error::RoleDoesNotExistForUserOrGroup)
+            );
+            rbac.activate_role(session_id, [10;32]).unwrap();
+            assert_eq!(rbac.activate_role(session_id, [10;32]), Err(Error::RoleActiveAlready));
+
+            // The Role removed after the activation is not used
+            set_caller(default_accounts().alice);
+            rbac.remove_user_or_group_from_role([1;32], [10;32]).unwrap();
+            assert!(!rbac.check_session_access(session_id, [20;32]));
+        }
+
+        #[ink::test]
+        fn check_session_access_lockdown_fail() {
+            let mut rbac = RBAC::default();
+            let session_id = setup_session_roles(&mut rbac);
+            rbac.activate_role(session_id, [10;32]).unwrap();
+
+            set_caller(default_accounts().alice);
+            rbac.set_lockdown(true).unwrap();
+            set_caller(default_accounts().bob);
+            assert!(!rbac.check_session_access(session_id, [20;32]));
+        }
+
+        #[ink::test]
+        fn session_of_other_account_fail() {
+            let mut rbac = RBAC::default();
+            let session_id = setup_session_roles(&mut rbac);
+
+            set_caller(default_accounts().charlie);
+            assert_eq!(rbac.create_session([1;32]), Err(Error::AccountNotBound));
+            assert_eq!(rbac.activate_role(session_id, [10;32]), Err(Error::NotSessionOwner));
+            assert_eq!(rbac.close_session(session_id), Err(Error::NotSessionOwner));
         }
 
-        // Read User/Group Roles
-        #[ink(message)]
-        pub fn read_user_or_group_roles(&self, user_or_group_did: UserGroupDID) ->Vec<RoleDID> {
-            let mut vec_roles = Vec::new();
-            vec_roles.append(&mut self.get_role(&user_or_group_did));
+        #[ink::test]
+        fn session_expired_fail() {
+            let mut rbac = RBAC::default();
+            rbac.set_session_lifetime(1).unwrap();
+            let session_id = setup_session_roles(&mut rbac);
+            rbac.activate_role(session_id, [10;32]).unwrap();
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(rbac.check_session_access(session_id, [20;32]));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(!rbac.check_session_access(session_id, [20;32]));
+            assert_eq!(rbac.activate_role(session_id, [11;32]), Err(Error::SessionExpired));
+        }
 
-            // The User/Group DID isn't the same, so just try to get the roles
-            self.read_user_belongs(user_or_group_did)
-                .iter()
-                .for_each(|group| 
-                    vec_roles.append(&mut self.get_role(group))
+        #[ink::test]
+        fn role_max_members_works() {
+            let mut rbac = RBAC::default();
+            let role_did = [10;32];
+            rbac.set_role_max_members(role_did, Some(2)).unwrap();
+            rbac.add_user_or_group_to_role([1;32], role_did).unwrap();
+            rbac.add_user_or_group_to_role([2;32], role_did).unwrap();
+            assert_eq!(rbac.read_role_usage(role_did), (2, Some(2)));
+            assert_eq!(
+                rbac.add_user_or_group_to_role([3;32], role_did),
+                Err(Error::MemberLimitReached)
             );
 
-            self.read_group_belongs(user_or_group_did)
-                .iter()
-                .for_each(|group| 
-                    vec_roles.append(&mut self.get_role(group))
-            );
+            rbac.remove_user_or_group_from_role([1;32], role_did).unwrap();
+            assert_eq!(rbac.read_role_usage(role_did), (1, Some(2)));
+            assert_eq!(rbac.add_user_or_group_to_role([3;32], role_did), Ok(()));
 
-            vec_roles.into_iter()
-                .collect::<BTreeSet<_>>()
-                .into_iter()
-                .collect()
+            rbac.set_role_max_members(role_did, None).unwrap();
+            assert_eq!(rbac.add_user_or_group_to_role([4;32], role_did), Ok(()));
+            assert_eq!(rbac.read_role_usage(role_did), (3, None));
         }
-        
-
-        // Add Role to the Permission
-        #[ink(message)]
-        pub fn add_role_to_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
-            let permission = Permission{ id: permission_did};
-            let mut vec_permission = self.map_role_to_permission.get(&role_did).unwrap_or_else(Vec::new);
 
-            if vec_permission.contains(&permission) {
-                return Err(Error::RoleHasPermissionAlready);
-            }
-            vec_permission.push(permission);
-            self.map_role_to_permission.insert(role_did, &vec_permission);
-            Ok(())
+        #[ink::test]
+        fn role_max_members_group_fail() {
+            let mut rbac = RBAC::default();
+            let (role_did, group_did) = ([10;32], [3;32]);
+            rbac.set_role_max_members(role_did, Some(2)).unwrap();
+            rbac.add_user_to_group([1;32], group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, role_did).unwrap();
+            rbac.add_user_to_group([2;32], group_did).unwrap();
+            assert_eq!(rbac.read_role_usage(role_did), (2, Some(2)));
+
+            // The members of the Group count against the limit of its Role
+            assert_eq!(
+                rbac.add_user_to_group([4;32], group_did),
+                Err(Error::MemberLimitReached)
+            );
+            assert_eq!(
+                rbac.add_user_or_group_to_role([4;32], role_did),
+                Err(Error::MemberLimitReached)
+            );
+            // A member having the Role directly too is counted once
+            assert_eq!(rbac.add_user_or_group_to_role([1;32], role_did), Ok(()));
+            assert_eq!(rbac.read_role_usage(role_did), (2, Some(2)));
         }
 
-        // Remove Role from the Permission
-        #[ink(message)]
-        pub fn remove_role_from_permission(&mut self, role_did: RoleDID, permission_did: PermissionDID) -> Result<()> {
-            let mut perms = self.map_role_to_permission.get(&role_did).ok_or(Error::RoleDoesNotExist)?;
-            let index = perms
-                .iter()
-                .position(|r| r.id == permission_did)
-                .ok_or(Error::PermissionNotExistInRole)?;
+        #[ink::test]
+        fn group_max_members_works() {
+            let mut rbac = RBAC::default();
+            let group_did = [10;32];
+            rbac.set_group_max_members(group_did, Some(1)).unwrap();
+            rbac.add_user_to_group([1;32], group_did).unwrap();
+            assert_eq!(rbac.read_group_usage(group_did), (1, Some(1)));
+            assert_eq!(
+                rbac.add_user_to_group([2;32], group_did),
+                Err(Error::MemberLimitReached)
+            );
+            // Adding a member twice is still reported as such
+            assert_eq!(
+                rbac.add_user_to_group([1;32], group_did),
+                Err(Error::GroupHasUserOrGroupAlready)
+            );
 
-            perms.remove(index);
-            self.map_role_to_permission.insert(&role_did, &perms);
-            Ok(())
-        }
-          
-        // Read Permission for Roles
-        #[ink(message)]
-        pub fn read_permissions(&self, role_did: RoleDID) ->Vec<PermissionDID> {
-            self.map_role_to_permission.get(&role_did).unwrap_or_else(Vec::new)
-                .iter()
-                .map(|x| x.id )
-                .collect()
+            rbac.remove_user_from_group([1;32], group_did).unwrap();
+            assert_eq!(rbac.add_user_to_group([2;32], group_did), Ok(()));
         }
 
-        #[ink(message)]
-        pub fn check_access(&self, user_did: UserDID, permission_did: PermissionDID) -> bool {
-            self.read_user_or_group_roles(user_did)
-                .iter()
-                .any(|&role| {
-                    self.read_permissions(role).contains(&permission_did)
-            })
+        #[ink::test]
+        fn set_max_members_not_admin_fail() {
+            let mut rbac = RBAC::default();
+            set_caller(default_accounts().bob);
+            assert_eq!(rbac.set_role_max_members([10;32], Some(1)), Err(Error::NotAdmin));
+            assert_eq!(rbac.set_group_max_members([10;32], Some(1)), Err(Error::NotAdmin));
         }
-    }
- 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    
-    #[cfg(test)]
-    mod tests 
-    {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
 
-        /// Imports `ink_lang` so we can use `#[ink::test]`.
-        use ink_lang as ink;
+        #[ink::test]
+        fn approve_request_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.bind_account(accounts.charlie, [2;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
+            rbac.set_role_manager_permission([10;32], Some([21;32])).unwrap();
+
+            set_caller(accounts.bob);
+            let request_id = rbac.request_role([10;32], [7;32]).unwrap();
+            assert_eq!(rbac.request_role([10;32], [7;32]), Err(Error::RequestExistsAlready));
+            assert_eq!(rbac.read_role_requests([10;32]), vec![request_id]);
+            assert_eq!(rbac.approve_request(request_id), Err(Error::NotRoleManager));
+
+            set_caller(accounts.charlie);
+            rbac.approve_request(request_id).unwrap();
+            assert_eq!(rbac.get_role(&[1;32]), vec![[10;32]]);
+            assert_eq!(rbac.read_request(request_id), None);
+            assert!(rbac.read_role_requests([10;32]).is_empty());
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.request_role([10;32], [7;32]), Err(Error::UserOrGroupHasRoleAlready));
+        }
 
         #[ink::test]
-        fn add_single_user_to_group_works() {
+        fn reject_request_works() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
 
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
+            set_caller(accounts.bob);
+            let request_id = rbac.request_role([10;32], [7;32]).unwrap();
+            assert_eq!(rbac.reject_request(request_id), Err(Error::NotRoleManager));
 
-            let vec_group_in_user = rbac.read_user_belongs([2;32]);
-            assert_eq!(vec_group_in_user.len(), 1);
+            set_caller(accounts.alice);
+            rbac.reject_request(request_id).unwrap();
+            assert!(rbac.get_role(&[1;32]).is_empty());
+            assert_eq!(rbac.reject_request(request_id), Err(Error::RequestDoesNotExist));
         }
 
         #[ink::test]
-        fn add_single_user_to_groups_works() {
+        fn expired_request_fail() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
-            rbac.add_user_to_group([2;32], [4;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.set_request_lifetime(1).unwrap();
+
+            set_caller(accounts.bob);
+            let request_id = rbac.request_role([10;32], [7;32]).unwrap();
+            assert_eq!(rbac.remove_expired_request(request_id), Err(Error::RequestNotExpired));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+
+            set_caller(accounts.alice);
+            assert_eq!(rbac.approve_request(request_id), Err(Error::RequestExpired));
+            set_caller(accounts.django);
+            rbac.remove_expired_request(request_id).unwrap();
+            assert_eq!(rbac.read_request(request_id), None);
+        }
 
+        #[ink::test]
+        fn request_role_after_expiry_works() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.set_request_lifetime(1).unwrap();
+
+            set_caller(accounts.bob);
+            let expired_id = rbac.request_role([10;32], [7;32]).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+
+            // The expired request does not block a new one and is replaced by it
+            let request_id = rbac.request_role([10;32], [7;32]).unwrap();
+            assert_eq!(rbac.read_request(expired_id), None);
+            assert_eq!(rbac.read_role_requests([10;32]), vec![request_id]);
+            assert_eq!(rbac.request_role([10;32], [7;32]), Err(Error::RequestExistsAlready));
+        }
 
-            assert_eq!(
-                rbac.add_user_to_group([2;32], [3;32]),
-                Ok(())
-            );
+        #[ink::test]
+        fn sensitive_role_needs_proposal() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.add_admin(accounts.bob).unwrap();
+            assert_eq!(rbac.set_approval_policy(DEFAULT_TENANT, [10;32], vec![accounts.bob, accounts.charlie], 3), Err(Error::InvalidThreshold));
+            rbac.set_approval_policy(DEFAULT_TENANT, [10;32], vec![accounts.bob, accounts.charlie], 2).unwrap();
+
+            assert_eq!(rbac.add_user_or_group_to_role([1;32], [10;32]), Err(Error::ApprovalRequired));
+            assert_eq!(rbac.add_role_to_permission([10;32], [20;32]), Err(Error::ApprovalRequired));
+            assert_eq!(rbac.propose(PolicyOp::AddUserOrGroupToRole([1;32], [11;32])), Err(Error::ApprovalNotRequired));
+
+            set_caller(accounts.bob);
+            let proposal_id = rbac.propose(PolicyOp::AddUserOrGroupToRole([1;32], [10;32])).unwrap();
+            assert_eq!(rbac.read_proposal(proposal_id).unwrap().approvals, vec![accounts.bob]);
+            assert_eq!(rbac.approve_proposal(proposal_id), Err(Error::ProposalApprovedAlready));
+
+            set_caller(accounts.django);
+            assert_eq!(rbac.approve_proposal(proposal_id), Err(Error::NotApprover));
+
+            set_caller(accounts.charlie);
+            rbac.approve_proposal(proposal_id).unwrap();
+            assert_eq!(rbac.get_role(&[1;32]), vec![[10;32]]);
+            assert_eq!(rbac.read_proposal(proposal_id), None);
+        }
 
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
-            let vec_users_in_group = rbac.read_user_group([4;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
-            let vec_users_in_group = rbac.read_user_group([3;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
+        #[ink::test]
+        fn tenant_sensitive_role_needs_proposal() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.set_account_tenant(accounts.charlie, [7;32]).unwrap();
+            rbac.set_approval_policy([7;32], [10;32], vec![accounts.bob, accounts.charlie], 2).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
 
-            let vec_group_in_user = rbac.read_user_belongs([2;32]);
-            assert_eq!(vec_group_in_user.len(), 3);
+            set_caller(accounts.bob);
+            assert_eq!(rbac.read_approval_policy([10;32]).unwrap().threshold, 2);
+            assert_eq!(rbac.add_user_or_group_to_role([1;32], [10;32]), Err(Error::ApprovalRequired));
+            let proposal_id = rbac.propose(PolicyOp::AddUserOrGroupToRole([1;32], [10;32])).unwrap();
+            set_caller(accounts.charlie);
+            rbac.approve_proposal(proposal_id).unwrap();
+            assert_eq!(rbac.read_user_or_group_roles([1;32]), vec![[10;32]]);
+
+            set_caller(accounts.alice);
+            rbac.remove_approval_policy([7;32], [10;32]).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(rbac.read_approval_policy([10;32]), None);
         }
 
         #[ink::test]
-        fn add_users_to_single_group_works() {
+        fn group_with_sensitive_role_needs_proposal() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([1;32], [3;32]).unwrap();
-            rbac.add_user_to_group([2;32], [3;32]).unwrap();
+            let accounts = default_accounts();
+            let group_did = [3;32];
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.add_user_to_group([1;32], group_did).unwrap();
+            rbac.add_user_or_group_to_role(group_did, [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.set_approval_policy(DEFAULT_TENANT, [10;32], vec![accounts.bob, accounts.charlie], 2).unwrap();
 
-            let vec_users_in_group = rbac.read_user_group([3;32]);
-            assert_eq!(vec_users_in_group.len(), 2);
+            assert_eq!(rbac.add_user_to_group([2;32], group_did), Err(Error::ApprovalRequired));
+            assert_eq!(rbac.transfer_subject([1;32], [2;32], None), Err(Error::ApprovalRequired));
+            assert_eq!(rbac.read_user_group(group_did), vec![[1;32]]);
 
-            let vec_group_in_user = rbac.read_user_belongs([1;32]);
-            assert_eq!(vec_group_in_user.len(), 1);
-            let vec_group_in_user = rbac.read_user_belongs([2;32]);
-            assert_eq!(vec_group_in_user.len(), 1);
+            set_caller(accounts.bob);
+            let proposal_id = rbac.propose(PolicyOp::AddUserToGroup([2;32], group_did)).unwrap();
+            set_caller(accounts.charlie);
+            rbac.approve_proposal(proposal_id).unwrap();
+            assert_eq!(rbac.read_user_group(group_did), vec![[1;32], [2;32]]);
         }
 
         #[ink::test]
-        fn add_single_user_to_group_already() {
+        fn cancel_proposal_works() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
-            assert_eq!(
-                rbac.add_user_to_group([2;32], [1;32]),
-                Err(Error::GroupHasUserOrGroupAlready)
-            );
+            let accounts = default_accounts();
+            rbac.set_approval_policy(DEFAULT_TENANT, [20;32], vec![accounts.bob, accounts.charlie], 2).unwrap();
+            rbac.set_proposal_lifetime(1).unwrap();
+            let proposal_id = rbac.propose(PolicyOp::AddRoleToPermission([10;32], [20;32])).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.cancel_proposal(proposal_id), Err(Error::NotProposer));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert_eq!(rbac.approve_proposal(proposal_id), Err(Error::ProposalExpired));
+            rbac.cancel_proposal(proposal_id).unwrap();
+            assert_eq!(rbac.read_proposal(proposal_id), None);
+            assert!(rbac.read_permissions([10;32]).is_empty());
         }
 
         #[ink::test]
-        fn remove_users_from_group_works() {
+        fn break_glass_works() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
-            rbac.add_user_to_group([3;32], [1;32]).unwrap();
-            rbac.add_user_to_group([4;32], [1;32]).unwrap();
-
-            // remove user from group
-            assert_eq!(
-                rbac.remove_user_from_group([4;32], [1;32]),
-                Ok(())
-            );
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.allow_break_glass([1;32], [20;32], 1).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.break_glass([21;32], [7;32]), Err(Error::BreakGlassNotAllowed));
+            assert!(!rbac.check_access([1;32], [20;32]));
+            let break_glass_id = rbac.break_glass([20;32], [7;32]).unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
+            assert_eq!(rbac.read_unacknowledged_break_glass(0, 10), vec![break_glass_id]);
+            assert_eq!(rbac.acknowledge_break_glass(break_glass_id), Err(Error::NotAdmin));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(!rbac.check_access([1;32], [20;32]));
+
+            set_caller(accounts.alice);
+            rbac.acknowledge_break_glass(break_glass_id).unwrap();
+            assert!(rbac.read_unacknowledged_break_glass(0, 10).is_empty());
+            assert_eq!(rbac.read_break_glass_use(break_glass_id).unwrap().acknowledged_by, Some(accounts.alice));
+            assert_eq!(rbac.acknowledge_break_glass(break_glass_id), Err(Error::BreakGlassAcknowledgedAlready));
+        }
 
-            // new user count should be reduced 
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 2);
-            let vec_group_in_user = rbac.read_user_belongs([4;32]);
-            assert_eq!(vec_group_in_user.len(), 0);
+        #[ink::test]
+        fn acknowledge_break_glass_keeps_others() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.allow_break_glass([1;32], [20;32], 1).unwrap();
+
+            set_caller(accounts.bob);
+            let ids = (0..3)
+                .map(|_| rbac.break_glass([20;32], [7;32]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(rbac.read_unacknowledged_break_glass_count(), 3);
+            assert_eq!(rbac.read_unacknowledged_break_glass(1, 1), vec![ids[1]]);
+
+            set_caller(accounts.alice);
+            rbac.acknowledge_break_glass(ids[0]).unwrap();
+            assert_eq!(rbac.read_unacknowledged_break_glass(0, 10), vec![ids[2], ids[1]]);
+            rbac.acknowledge_break_glass(ids[1]).unwrap();
+            assert_eq!(rbac.read_unacknowledged_break_glass(0, 10), vec![ids[2]]);
+            assert_eq!(rbac.read_unacknowledged_break_glass_count(), 1);
         }
 
         #[ink::test]
-        fn remove_user_from_groups_works() {
+        fn pause_works() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([1;32], [2;32]).unwrap();
-            rbac.add_user_to_group([1;32], [3;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.set_guardian(Some(accounts.bob)).unwrap();
 
-            // remove user from group
-            assert_eq!(
-                rbac.remove_user_from_group([1;32], [2;32]),
-                Ok(())
-            );
+            set_caller(accounts.charlie);
+            assert_eq!(rbac.pause(), Err(Error::NotGuardian));
 
-            // new user count should be reduced 
-            let vec_users_in_group = rbac.read_user_group([3;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
-            assert_eq!(vec_users_in_group[0], [1;32]);
+            set_caller(accounts.bob);
+            rbac.pause().unwrap();
+            assert!(rbac.is_paused());
+
+            set_caller(accounts.alice);
+            assert_eq!(rbac.add_user_to_group([1;32], [2;32]), Err(Error::ContractPaused));
+            assert_eq!(rbac.add_admin(accounts.charlie), Err(Error::ContractPaused));
+            rbac.unpause().unwrap();
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
         }
 
         #[ink::test]
-        fn remove_user_from_groups_and_added_again() {
+        fn lockdown_works() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([1;32], [2;32]).unwrap();
-            rbac.add_user_to_group([1;32], [3;32]).unwrap();
-            rbac.remove_user_from_group([1;32], [2;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            set_caller(accounts.bob);
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [21;32]).unwrap();
+            set_caller(accounts.alice);
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [21;32]).unwrap();
+            rbac.set_lockdown_allowed(DEFAULT_TENANT, [21;32], true).unwrap();
+
+            rbac.set_lockdown(true).unwrap();
+            assert!(!rbac.check_access([1;32], [20;32]));
+            assert!(rbac.check_access([1;32], [21;32]));
+            // The Permission is allowed in its tenant only
+            assert!(!rbac.check_access_in_tenant([7;32], [1;32], [21;32]));
+            assert!(!rbac.is_lockdown_allowed([7;32], [21;32]));
+            rbac.set_lockdown(false).unwrap();
+            assert!(rbac.check_access_in_tenant([7;32], [1;32], [21;32]));
+            rbac.set_lockdown(true).unwrap();
+            assert_eq!(rbac.read_permissions([10;32]), vec![[20;32], [21;32]]);
+
+            rbac.set_lockdown(false).unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
+        }
 
-            assert_eq!(
-                rbac.add_user_to_group([1;32], [2;32]),
-                Ok(())
-            );
+        #[ink::test]
+        fn tenants_are_isolated() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            assert_eq!(rbac.bind_account(accounts.bob, [1;32]), Err(Error::WrongTenant));
 
-            let vec_users_in_group = rbac.read_user_group([2;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
-            assert_eq!(vec_users_in_group[0], [1;32]);
-            let vec_users_in_group = rbac.read_user_group([3;32]);
-            assert_eq!(vec_users_in_group.len(), 1);
-            assert_eq!(vec_users_in_group[0], [1;32]);
+            set_caller(accounts.bob);
+            assert_eq!(rbac.read_account_tenant(accounts.bob), [7;32]);
+            assert!(!rbac.check_access([1;32], [20;32]));
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
+            assert_eq!(rbac.read_user_or_group_roles([1;32]), vec![[11;32]]);
+            assert!(rbac.check_access([1;32], [21;32]));
+
+            set_caller(accounts.charlie);
+            assert_eq!(rbac.read_user_or_group_roles([1;32]), vec![[10;32]]);
+            assert!(!rbac.check_access([1;32], [21;32]));
+            assert!(rbac.check_access_in_tenant([7;32], [1;32], [21;32]));
+            assert!(rbac.check_access_in_tenant(DEFAULT_TENANT, [1;32], [20;32]));
         }
 
-        //remove_user_from_group_wrong_group() when wrong group id passed
         #[ink::test]
-        fn remove_user_from_empty_group_fail() {
+        fn tenant_keys_cannot_be_forged() {
             let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            // The DIDs of another tenant cannot be addressed by passing their hashed keys
+            let hashed = |did: DIDType| {
+                let mut key = [0u8; 32];
+                ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(&([7u8;32], did), &mut key);
+                key
+            };
+            rbac.add_user_or_group_to_role(hashed([1;32]), hashed([10;32])).unwrap();
+            rbac.add_user_or_group_to_role(hashed([1;32]), [10;32]).unwrap();
+            rbac.add_role_to_permission(hashed([10;32]), [20;32]).unwrap();
+
+            set_caller(accounts.bob);
+            assert!(rbac.read_user_or_group_roles([1;32]).is_empty());
+            assert!(!rbac.check_access([1;32], [20;32]));
+            assert!(!rbac.check_access_in_tenant([7;32], [1;32], [20;32]));
+        }
 
-            assert_eq!(
-                rbac.remove_user_from_group([2;32], [3;32]),
-                Err(Error::GroupDoesNotExist)
-            );
+        #[ink::test]
+        fn tenant_tokens_are_isolated() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            let token = rbac.issue_token([1;32], [20;32], 10).unwrap();
+            assert_eq!(token.tenant, DEFAULT_TENANT);
+
+            set_caller(accounts.bob);
+            // The token is checked in its own tenant, not in the one of the caller
+            assert!(rbac.verify_token(token.clone()));
+            assert_eq!(rbac.revoke_token(token.clone()), Err(Error::WrongTenant));
+            assert_eq!(rbac.issue_token([1;32], [20;32], 10), Err(Error::AccessNotGranted));
+            // A token moved to another tenant was never issued there
+            let mut moved = token.clone();
+            moved.tenant = [7;32];
+            assert!(!rbac.verify_token(moved));
+
+            set_caller(accounts.alice);
+            rbac.revoke_token(token.clone()).unwrap();
+            assert_eq!(rbac.read_revoked_tokens(DEFAULT_TENANT, 0, 10), vec![token.hash()]);
+            assert!(rbac.read_revoked_tokens([7;32], 0, 10).is_empty());
         }
 
-        //remove_user_from_group_wrong_group() when wrong group id passed
         #[ink::test]
-        fn remove_user_from_group_wrong_group_fail() {
+        fn global_settings_only_owner() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
-            rbac.add_user_to_group([3;32], [1;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.set_session_lifetime(1), Err(Error::NotOwner));
+            assert_eq!(rbac.set_request_lifetime(1), Err(Error::NotOwner));
+            assert_eq!(rbac.set_callback_gas_limit(1), Err(Error::NotOwner));
+        }
 
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 2);
+        #[ink::test]
+        fn tenant_sessions_are_isolated() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.set_account_tenant(accounts.charlie, [7;32]).unwrap();
+            rbac.add_admin(accounts.charlie).unwrap();
 
-            // remove user from group where group id is wrong
-            assert_eq!(
-                rbac.remove_user_from_group([2;32], [4;32]),
-                Err(Error::GroupDoesNotExist)
-            );
+            set_caller(accounts.charlie);
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_ssd_constraint(vec![[11;32], [12;32]], 2).unwrap();
+            set_caller(accounts.bob);
+            let session_id = rbac.create_session([1;32]).unwrap();
+            rbac.activate_role(session_id, [10;32]).unwrap();
+
+            // The session keeps the roles of its tenant, whoever checks it
+            set_caller(accounts.alice);
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            assert!(rbac.check_session_access(session_id, [20;32]));
+            assert_eq!(rbac.read_session(session_id), None);
+            assert_eq!(rbac.read_ssd_constraint(0), None);
+            set_caller(accounts.charlie);
+            assert!(rbac.read_ssd_constraint(0).is_some());
         }
 
+        #[ink::test]
+        fn with_owner_works() {
+            let accounts = default_accounts();
+            let rbac = RBAC::with_owner(accounts.bob, vec![
+                PolicyOp::AddUserOrGroupToRole([1;32], [10;32]),
+                PolicyOp::AddRoleToPermission([10;32], [20;32]),
+            ]);
+            assert!(rbac.is_admin(accounts.bob));
+            assert!(!rbac.is_admin(accounts.alice));
+            assert!(rbac.check_access([1;32], [20;32]));
+            assert_eq!(rbac.policy_version(), 2);
+        }
 
-        //user's and group's DID are the same
         #[ink::test]
-        fn user_group_did_are_same() {
+        fn scope_admin_works() {
             let mut rbac = RBAC::default();
-            assert_eq!(rbac.add_user_to_group([1;32], [1;32]),
-                Err(Error::UserGroupAreSame)
-            );
+            let accounts = default_accounts();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+            rbac.add_scope_admin(AdminScope::Group([2;32]), [1;32]).unwrap();
+            rbac.add_scope_admin(AdminScope::Role([10;32]), [1;32]).unwrap();
+            assert_eq!(rbac.add_scope_admin(AdminScope::Role([10;32]), [1;32]), Err(Error::ScopeAdminExistsAlready));
+            assert_eq!(rbac.read_scope_admins(AdminScope::Group([2;32])), vec![[1;32]]);
+            assert_eq!(rbac.read_user_scopes([1;32]), vec![AdminScope::Group([2;32]), AdminScope::Role([10;32])]);
+
+            set_caller(accounts.bob);
+            rbac.add_user_to_group([3;32], [2;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [10;32]).unwrap();
+            assert_eq!(rbac.add_user_to_group([3;32], [4;32]), Err(Error::NotAdmin));
+            assert_eq!(rbac.add_user_or_group_to_role([2;32], [11;32]), Err(Error::NotAdmin));
+            assert_eq!(rbac.add_role_to_permission([10;32], [20;32]), Err(Error::NotAdmin));
+            rbac.remove_user_from_group([3;32], [2;32]).unwrap();
+
+            set_caller(accounts.alice);
+            rbac.remove_scope_admin(AdminScope::Group([2;32]), [1;32]).unwrap();
+            assert_eq!(rbac.read_user_scopes([1;32]), vec![AdminScope::Role([10;32])]);
+            set_caller(accounts.bob);
+            assert_eq!(rbac.add_user_to_group([3;32], [2;32]), Err(Error::NotAdmin));
         }
 
-        //user's and group's DID are the same
         #[ink::test]
-        fn user_group_did_conflict() {
+        fn check_access_on_works() {
             let mut rbac = RBAC::default();
-            let (user_did, group_did) = ([2;32], [1;32]);
-            rbac.add_user_to_group(user_did, group_did).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [11;32]).unwrap();
+            rbac.add_role_to_permission_on([10;32], [20;32], [30;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [20;32]).unwrap();
+            assert_eq!(rbac.add_role_to_permission_on([10;32], [20;32], [30;32]), Err(Error::RoleHasPermissionAlready));
+            assert_eq!(rbac.read_role_resources([10;32]), vec![[30;32]]);
+
+            assert!(rbac.check_access_on([1;32], [20;32], [30;32]));
+            assert!(!rbac.check_access_on([1;32], [20;32], [31;32]));
+            assert!(!rbac.check_access([1;32], [20;32]));
+            assert!(rbac.check_access_on([2;32], [20;32], [31;32]));
+
+            rbac.remove_role_from_permission_on([10;32], [20;32], [30;32]).unwrap();
+            assert!(!rbac.check_access_on([1;32], [20;32], [30;32]));
+            assert!(rbac.read_role_resources([10;32]).is_empty());
+            assert_eq!(rbac.remove_role_from_permission_on([10;32], [20;32], [30;32]), Err(Error::PermissionNotExistInRole));
+        }
 
-            assert_eq!(rbac.add_user_to_group(group_did, [3;32]),
-                Err(Error::UserGroupAreSame)
-            );
+        #[ink::test]
+        fn resource_tree_works() {
+            let mut rbac = RBAC::default();
+            // campus [30] -> building [31] -> floor [32] -> door [33], building [31] -> door [34]
+            rbac.add_child_resource([30;32], [31;32]).unwrap();
+            rbac.add_child_resource([31;32], [32;32]).unwrap();
+            rbac.add_child_resource([32;32], [33;32]).unwrap();
+            rbac.add_child_resource([31;32], [34;32]).unwrap();
+            assert_eq!(rbac.add_child_resource([30;32], [34;32]), Err(Error::ResourceHasParentAlready));
+            assert_eq!(rbac.add_child_resource([33;32], [30;32]), Err(Error::ResourceCycle));
+            assert_eq!(rbac.add_child_resource([35;32], [35;32]), Err(Error::ResourceCycle));
+            assert_eq!(rbac.read_resource_subtree([31;32]), vec![[32;32], [34;32], [33;32]]);
 
-            assert_eq!(rbac.add_user_to_group([3;32], user_did),
-                Err(Error::UserGroupAreSame)
-            );
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission_on([10;32], [20;32], [32;32]).unwrap();
+            assert!(rbac.check_access_on([1;32], [20;32], [33;32]));
+            assert!(rbac.check_access_on([1;32], [20;32], [32;32]));
+            assert!(!rbac.check_access_on([1;32], [20;32], [31;32]));
+            assert!(!rbac.check_access_on([1;32], [20;32], [34;32]));
+
+            assert_eq!(rbac.remove_child_resource([31;32], [33;32]), Err(Error::ResourceNotChild));
+            rbac.remove_child_resource([32;32], [33;32]).unwrap();
+            assert!(!rbac.check_access_on([1;32], [20;32], [33;32]));
+            assert_eq!(rbac.read_resource_parent([33;32]), None);
         }
 
-        //remove_user_from_group_wrong_user() when wrong user id passed
         #[ink::test]
-        fn remove_user_from_group_wrong_user() {
+        fn resource_tree_too_deep_fail() {
             let mut rbac = RBAC::default();
-            rbac.add_user_to_group([2;32], [1;32]).unwrap();
-            rbac.add_user_to_group([3;32], [1;32]).unwrap();
+            // A chain of MAX_RESOURCE_DEPTH + 1 resources, the last one has MAX_RESOURCE_DEPTH ancestors
+            let chain = (0..=MAX_RESOURCE_DEPTH as u8).map(|i| [100 + i; 32]).collect::<Vec<_>>();
+            for pair in chain.windows(2) {
+                rbac.add_child_resource(pair[0], pair[1]).unwrap();
+            }
+            assert_eq!(rbac.add_child_resource(chain[MAX_RESOURCE_DEPTH], [30;32]), Err(Error::ResourceTreeTooDeep));
 
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 2);
+            // A resource with two levels below it fits under the resource with 13 ancestors at most
+            rbac.add_child_resource([30;32], [31;32]).unwrap();
+            rbac.add_child_resource([31;32], [32;32]).unwrap();
+            assert_eq!(rbac.add_child_resource(chain[MAX_RESOURCE_DEPTH - 2], [30;32]), Err(Error::ResourceTreeTooDeep));
+            assert_eq!(rbac.add_child_resource(chain[MAX_RESOURCE_DEPTH - 3], [30;32]), Ok(()));
 
-            // remove user from group where group id is wrong
-            assert_eq!(
-                rbac.remove_user_from_group([4;32], [1;32]),
-                Err(Error::UserOrGroupDoesNotExistInGroup)
-            );
+            // The grant on the root still reaches the deepest descendant
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission_on([10;32], [20;32], chain[0]).unwrap();
+            assert!(rbac.check_access_on([1;32], [20;32], [32;32]));
         }
 
         #[ink::test]
-        fn read_user_group_empty_works() {
-            let rbac = RBAC::default();
-            
-            // pass wrong group id
-            let vec_users_in_group = rbac.read_user_group([1;32]);
-            assert_eq!(vec_users_in_group.len(), 0);
-            let vec_group_in_user = rbac.read_user_belongs([1;32]);
-            assert_eq!(vec_group_in_user.len(), 0);
+        fn check_access_with_context_works() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            assert_eq!(rbac.set_permission_condition([10;32], [21;32], None), Err(Error::PermissionNotExistInRole));
+            assert_eq!(rbac.set_permission_condition([10;32], [20;32], Some(Condition { any_of: vec![vec![]] })), Err(Error::InvalidCondition));
+
+            // zone of the device == 1 and clearance of the User >= 3, or the context has the override set
+            let condition = Condition {
+                any_of: vec![
+                    vec![
+                        Comparison { left: Operand::Context([50;32]), op: CmpOp::Eq, right: Operand::Const(1) },
+                        Comparison { left: Operand::Subject([51;32]), op: CmpOp::Ge, right: Operand::Const(3) },
+                    ],
+                    vec![
+                        Comparison { left: Operand::Context([52;32]), op: CmpOp::Ne, right: Operand::Const(0) },
+                    ],
+                ],
+            };
+            rbac.set_permission_condition([10;32], [20;32], Some(condition.clone())).unwrap();
+            assert_eq!(rbac.read_permission_condition([10;32], [20;32]), Some(condition));
+
+            assert!(!rbac.check_access([1;32], [20;32]));
+            assert!(!rbac.check_access_with_context([1;32], [20;32], vec![([50;32], 1)]));
+            rbac.set_subject_attribute([1;32], [51;32], Some(3)).unwrap();
+            assert!(rbac.check_access_with_context([1;32], [20;32], vec![([50;32], 1)]));
+            assert!(!rbac.check_access_with_context([1;32], [20;32], vec![([50;32], 2)]));
+            assert!(rbac.check_access_with_context([1;32], [20;32], vec![([52;32], 1)]));
+
+            rbac.set_permission_condition([10;32], [20;32], None).unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
         }
 
-        // Assign single roles to single user/group: one to one
         #[ink::test]
-        fn assign_single_role_to_user_or_group() {
+        fn second_of_day_condition_works() {
             let mut rbac = RBAC::default();
             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-
-            let vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 1);
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            // Office hours, 8:00 to 18:00 UTC
+            rbac.set_permission_condition([10;32], [20;32], Some(Condition {
+                any_of: vec![vec![
+                    Comparison { left: Operand::SecondOfDay, op: CmpOp::Ge, right: Operand::Const(8 * 3600) },
+                    Comparison { left: Operand::SecondOfDay, op: CmpOp::Lt, right: Operand::Const(18 * 3600) },
+                ]],
+            })).unwrap();
+            // The off-chain blocks start at timestamp 0, midnight
+            assert!(!rbac.check_access([1;32], [20;32]));
         }
 
         #[ink::test]
-        fn assign_single_role_to_user_already() {
+        fn schedules_work() {
             let mut rbac = RBAC::default();
             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-            assert_eq!(
-                rbac.add_user_or_group_to_role([1;32], [10;32]),
-                Err(Error::UserOrGroupHasRoleAlready),
-            );
+            rbac.add_user_to_group([2;32], [3;32]).unwrap();
+            rbac.add_user_or_group_to_role([3;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
+            assert_eq!(rbac.add_schedule(Schedule { weekdays: 0x1f, windows: vec![TimeWindow { start: 10, end: 10 }] }), Err(Error::InvalidSchedule));
+
+            // The off-chain blocks start at midnight of Thursday 1 January 1970
+            let thursday_night = rbac.add_schedule(Schedule {
+                weekdays: 1 << 3,
+                windows: vec![TimeWindow { start: 0, end: 3600 }],
+            }).unwrap();
+            let weekday_evenings = rbac.add_schedule(Schedule {
+                weekdays: 0x1f,
+                windows: vec![TimeWindow { start: 18 * 3600, end: 22 * 3600 }],
+            }).unwrap();
+            assert_eq!(rbac.set_assignment_schedule([1;32], [11;32], Some(thursday_night)), Err(Error::RoleDoesNotExistForUserOrGroup));
+            assert_eq!(rbac.set_permission_schedule([10;32], [20;32], Some(7)), Err(Error::ScheduleDoesNotExist));
+
+            rbac.set_permission_schedule([10;32], [20;32], Some(thursday_night)).unwrap();
+            rbac.set_assignment_schedule([3;32], [11;32], Some(weekday_evenings)).unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
+            assert!(!rbac.check_access([2;32], [21;32]));
+            // The roles are still read with their schedules
+            assert_eq!(rbac.read_user_or_group_roles([2;32]), vec![[11;32]]);
+
+            rbac.update_schedule(thursday_night, Schedule {
+                weekdays: 1 << 4,
+                windows: vec![TimeWindow { start: 0, end: 3600 }],
+            }).unwrap();
+            assert!(!rbac.check_access([1;32], [20;32]));
+            rbac.set_permission_schedule([10;32], [20;32], None).unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
         }
 
-        // Assign muliple roles to single user/group: many to one
         #[ink::test]
-        fn assign_roles_to_user_or_group() {
+        fn tenant_schedules_are_isolated() {
             let mut rbac = RBAC::default();
-            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
-            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            let schedule = Schedule { weekdays: 0x7f, windows: vec![TimeWindow { start: 0, end: 3600 }] };
+            let schedule_id = rbac.add_schedule(schedule.clone()).unwrap();
 
-            let vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 3);
+            set_caller(accounts.bob);
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            assert_eq!(rbac.read_schedule(schedule_id), None);
+            assert_eq!(rbac.update_schedule(schedule_id, schedule), Err(Error::ScheduleDoesNotExist));
+            assert_eq!(rbac.set_assignment_schedule([1;32], [10;32], Some(schedule_id)), Err(Error::ScheduleDoesNotExist));
+            assert_eq!(rbac.set_permission_schedule([10;32], [20;32], Some(schedule_id)), Err(Error::ScheduleDoesNotExist));
         }
 
         #[ink::test]
-        fn assign_role_to_users_or_groups() {
+        fn consume_access_works() {
             let mut rbac = RBAC::default();
+            let accounts = default_accounts();
             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-            rbac.add_user_or_group_to_role([2;32], [10;32]).unwrap();
-            rbac.add_user_or_group_to_role([3;32], [10;32]).unwrap();
-
-            let vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 1);
-            let vec_roles = rbac.read_user_or_group_roles([2;32]);
-            assert_eq!(vec_roles.len(), 1);
-            let vec_roles = rbac.read_user_or_group_roles([3;32]);
-            assert_eq!(vec_roles.len(), 1);
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.set_access_quota([1;32], [20;32], Some(2)).unwrap();
+            rbac.set_consumer(accounts.django, true).unwrap();
+            assert_eq!(rbac.consume_access([1;32], [21;32]), Err(Error::AccessNotGranted));
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.consume_access([1;32], [20;32]), Err(Error::NotConsumer));
+
+            set_caller(accounts.django);
+            rbac.consume_access([1;32], [20;32]).unwrap();
+            rbac.consume_access([1;32], [20;32]).unwrap();
+            assert_eq!(rbac.read_access_quota([1;32], [20;32]), Some(0));
+            assert_eq!(rbac.consume_access([1;32], [20;32]), Err(Error::QuotaExhausted));
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+
+            set_caller(accounts.alice);
+            rbac.set_access_quota([1;32], [20;32], Some(1)).unwrap();
+            rbac.consume_access([1;32], [20;32]).unwrap();
+            rbac.set_access_quota([1;32], [20;32], None).unwrap();
+            rbac.consume_access([1;32], [20;32]).unwrap();
         }
 
         #[ink::test]
-        fn assign_multiple_role_to_user_with_group() {
+        fn tenant_consumers_are_isolated() {
             let mut rbac = RBAC::default();
-            let user_did = [1;32];
-            let group_did = [2;32];
-            let user_role_did = [10;32];
-            let group_role_did = [11;32];
-            rbac.add_user_or_group_to_role(user_did, user_role_did).unwrap();
-            rbac.add_user_to_group(user_did, group_did).unwrap();
-            rbac.add_user_or_group_to_role(group_did, group_role_did).unwrap();
-            rbac.add_user_or_group_to_role(group_did, user_role_did).unwrap();
-
-            let vec_roles = rbac.read_user_or_group_roles(user_did);
-            assert_eq!(vec_roles.len(), 2);
+            let accounts = default_accounts();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.set_access_quota([1;32], [20;32], Some(2)).unwrap();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            rbac.set_account_tenant(accounts.django, [7;32]).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.set_consumer(accounts.eve, true), Err(Error::WrongTenant));
+            rbac.set_consumer(accounts.django, true).unwrap();
+            set_caller(accounts.alice);
+            rbac.set_account_tenant(accounts.django, DEFAULT_TENANT).unwrap();
+            assert!(!rbac.is_consumer(accounts.django));
+
+            set_caller(accounts.django);
+            assert_eq!(rbac.consume_access([1;32], [20;32]), Err(Error::NotConsumer));
+            assert_eq!(rbac.read_access_quota([1;32], [20;32]), Some(2));
         }
 
         #[ink::test]
-        fn remove_role_from_single_user() {
+        fn check_and_record_access_works() {
             let mut rbac = RBAC::default();
+            let accounts = default_accounts();
             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.bind_account(accounts.bob, [1;32]).unwrap();
+
+            set_caller(accounts.charlie);
+            assert_eq!(rbac.check_and_record_access([1;32], [20;32]), Err(Error::NotConsumer));
+            assert_eq!(rbac.read_audit_count(), 0);
+
+            set_caller(accounts.bob);
+            assert_eq!(rbac.check_and_record_access([2;32], [20;32]), Err(Error::NotConsumer));
+            assert_eq!(rbac.check_and_record_access([1;32], [20;32]), Ok(true));
+            assert_eq!(rbac.check_and_record_access([1;32], [21;32]), Ok(false));
+            assert_eq!(rbac.read_audit_count(), 2);
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+
+            let log = rbac.read_audit_log(0, 10);
+            assert_eq!(log.len(), 2);
+            assert_eq!(log[1].0, 1);
+            assert_eq!(log[1].1.caller, accounts.bob);
+            assert!(log[0].1.granted);
+            assert!(!log[1].1.granted);
+            assert_eq!(rbac.read_audit_log(1, 10).len(), 1);
+            assert_eq!(rbac.read_audit_log(0, 1).len(), 1);
+        }
 
-            // remove user/group from role
-            assert_eq!(
-                rbac.remove_user_or_group_from_role([1;32], [10;32]),
-                Ok(())
-            );
-
-            // new user count should be reduced 
-            let vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 0)
+        #[ink::test]
+        fn audit_log_overwrites_oldest() {
+            let mut rbac = RBAC::default();
+            for _ in 0..AUDIT_LOG_CAPACITY + 2 {
+                rbac.check_and_record_access([1;32], [20;32]).unwrap();
+            }
+            let log = rbac.read_audit_log(0, MAX_AUDIT_PAGE);
+            assert_eq!(log.len(), MAX_AUDIT_PAGE as usize);
+            assert_eq!(log[0].0, 2);
+            assert!(rbac.read_audit_log(AUDIT_LOG_CAPACITY + 2, 10).is_empty());
         }
 
         #[ink::test]
-        fn remove_role_from_user_or_group_works() {
+        fn read_changes_since_works() {
             let mut rbac = RBAC::default();
+            let accounts = default_accounts();
             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
-            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
-
-            let mut vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 3);
+            rbac.set_guardian(Some(accounts.bob)).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            assert_eq!(rbac.policy_version(), 3);
+
+            let changes = rbac.read_changes_since(0).unwrap();
+            assert_eq!(changes.len(), 3);
+            assert_eq!(changes[0].op, ChangeOp::Policy(PolicyOp::AddUserOrGroupToRole([1;32], [10;32])));
+            assert_eq!(changes[0].caller, accounts.alice);
+            assert_eq!(changes[1].op, ChangeOp::Message(selector_bytes!("set_guardian")));
+            assert_eq!(changes[2].version, 3);
+            assert_eq!(rbac.read_changes_since(2).unwrap().len(), 1);
+            assert!(rbac.read_changes_since(3).unwrap().is_empty());
+
+            // Failed changes are not recorded
+            assert!(rbac.add_role_to_permission([10;32], [20;32]).is_err());
+            assert_eq!(rbac.policy_version(), 3);
+        }
 
-            // remove user/group from role
-            assert_eq!(
-                rbac.remove_user_or_group_from_role([1;32], [12;32]),
-                Ok(())
-            );
+        #[ink::test]
+        fn policy_changes_are_recorded() {
+            let mut rbac = RBAC::default();
+            let accounts = default_accounts();
+            rbac.set_role_template([30;32], vec![[20;32], [21;32]]).unwrap();
+            rbac.instantiate_role_template([30;32], [10;32]).unwrap();
+            rbac.clone_role([10;32], [11;32]).unwrap();
+            assert_eq!(rbac.policy_version(), 5);
+            let changes = rbac.read_changes_since(1).unwrap();
+            assert_eq!(changes[0].op, ChangeOp::Policy(PolicyOp::AddRoleToPermission([10;32], [20;32])));
+            assert_eq!(changes[3].op, ChangeOp::Policy(PolicyOp::AddRoleToPermission([11;32], [21;32])));
 
-            // new user count should be reduced 
-            vec_roles = rbac.read_user_or_group_roles([1;32]);
-            assert_eq!(vec_roles.len(), 2)
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.bind_account(accounts.alice, [1;32]).unwrap();
+            assert_eq!(rbac.read_changes_since(6).unwrap()[0].op, ChangeOp::Message(selector_bytes!("bind_account")));
+
+            // Using the access is not a change, so nobody can flood the change log with it
+            rbac.issue_token([1;32], [20;32], 10).unwrap();
+            rbac.create_session([1;32]).unwrap();
+            rbac.consume_access([1;32], [20;32]).unwrap();
+            set_caller(accounts.eve);
+            assert_eq!(rbac.issue_token([1;32], [20;32], 10), Err(Error::NotConsumer));
+            assert_eq!(rbac.policy_version(), 7);
         }
 
-         //remove_user_or_group_from_role_wrong_user_group() when wrong user/group id passed
-         #[ink::test]
-         fn remove_user_or_group_from_role_wrong_user_group() {
-             let mut rbac = RBAC::default();
-             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-             rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
- 
-             // delete user/group from role where group id is wrong
-             assert_eq!(
-                 rbac.remove_user_or_group_from_role([2;32], [10;32]),
-                 Err(Error::UserOrGroupDoesNotExist)
-             );
-         }
- 
-         //remove_user_or_group_from_role_wrong_role() when wrong role id passed
-         #[ink::test]
-         fn remove_user_or_group_from_role_wrong_role() {
-             let mut rbac = RBAC::default();
-             rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
-             rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
- 
-             // remove user/group from role where role id is wrong
-             assert_eq!(
-                 rbac.remove_user_or_group_from_role([1;32], [12;32]),
-                 Err(Error::RoleDoesNotExistForUserOrGroup)
-             );
-         }
- 
-         // Assign single permission to single role: one to one
         #[ink::test]
-        fn assign_single_permission_to_role() {
+        fn tenant_changes_are_isolated() {
             let mut rbac = RBAC::default();
-            let perm_1 = [20;32];
-            let role_1 = [10;32];
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
+            let accounts = default_accounts();
+            rbac.set_account_tenant(accounts.bob, [7;32]).unwrap();
+            rbac.add_admin(accounts.bob).unwrap();
+            assert_eq!(rbac.policy_version(), 2);
 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 1);
+            set_caller(accounts.bob);
+            assert_eq!(rbac.policy_version(), 0);
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            assert_eq!(rbac.policy_version(), 1);
+            assert_eq!(rbac.read_changes_since(0).unwrap().len(), 1);
+
+            set_caller(accounts.alice);
+            assert_eq!(rbac.policy_version(), 2);
+            assert!(rbac.read_changes_since(0).unwrap()
+                .iter()
+                .all(|change| matches!(change.op, ChangeOp::Message(_))));
         }
 
         #[ink::test]
-        fn assign_single_permission_to_role_multiple_times() {
+        fn read_changes_since_pruned_fail() {
             let mut rbac = RBAC::default();
-            let perm_1 = [20;32];
-            let role_1 = [10;32];
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            assert_eq!(
-                rbac.add_role_to_permission(role_1, perm_1),
-                Err(Error::RoleHasPermissionAlready)
-            );
+            for _ in 0..CHANGE_LOG_CAPACITY + 1 {
+                rbac.set_lockdown(false).unwrap();
+            }
+            assert_eq!(rbac.read_changes_since(0), Err(Error::ChangesPruned));
+            let changes = rbac.read_changes_since(1).unwrap();
+            assert_eq!(changes.len(), MAX_CHANGE_PAGE as usize);
+            assert_eq!(changes[0].version, 2);
         }
 
-        // Assign muliple permission to single role: many to one
         #[ink::test]
-        fn assign_multiple_permission_to_role() {
+        fn check_access_at_works() {
             let mut rbac = RBAC::default();
-            let role_1 = [10;32];
-            let (perm_1, perm_2, perm_3) = ([20;32], [21;32], [22;32]);
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            rbac.add_role_to_permission(role_1, perm_2).unwrap();
-            rbac.add_role_to_permission(role_1, perm_3).unwrap();
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            rbac.remove_user_from_group([1;32], [2;32]).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 3);
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 0), Ok(true));
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 1), Ok(false));
+            assert_eq!(rbac.check_access_at([1;32], [21;32], 1), Ok(false));
+            assert_eq!(rbac.check_access_at([1;32], [21;32], 2), Ok(true));
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 3), Err(Error::HistoryNotKept));
         }
 
-        // Assign muliple permission to muliple role: many to many
         #[ink::test]
-        fn assign_single_permission_to_multiple_role() {
+        fn edge_history_is_capped() {
             let mut rbac = RBAC::default();
-            let (role_1, role_2) = ([10;32], [11;32]);
-            let perm_1 = [20;32];
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            rbac.add_role_to_permission(role_2, perm_1).unwrap();
-
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 1);
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            for _ in 0..MAX_REMOVED_EDGES + 1 {
+                rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+                rbac.remove_role_from_permission([10;32], [20;32]).unwrap();
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            }
+            let key = (EdgeKind::Permission, scoped_key(&DEFAULT_TENANT, &[10;32]));
+            assert_eq!(rbac.map_edge_history.get(key).unwrap().len(), MAX_REMOVED_EDGES);
 
-            let vec_roles = rbac.read_permissions(role_2);
-            assert_eq!(vec_roles.len(), 1);
+            // The first link was dropped, so the blocks before its removal are not kept
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 0), Err(Error::HistoryNotKept));
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 1), Ok(false));
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 2), Ok(true));
         }
 
         #[ink::test]
-        fn remove_signle_role_from_permission_works() {
+        fn history_retention_works() {
             let mut rbac = RBAC::default();
-            let role_1 = [10;32];
-            let perm_1 = [20;32];
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-
-            // delete role from permission
-            assert_eq!(
-                rbac.remove_role_from_permission(role_1, perm_1),
-                Ok(())
-            );
+            rbac.set_history_retention(1).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.remove_user_or_group_from_role([1;32], [10;32]).unwrap();
+            for _ in 0..3 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            }
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 1), Err(Error::HistoryNotKept));
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 2), Ok(false));
 
-            // new permission count should be reduced 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 0)
+            // The removed link is dropped by the next change of the User's links
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            assert_eq!(rbac.map_edge_history.get((EdgeKind::Assignment, scoped_key(&DEFAULT_TENANT, &[1;32]))).unwrap().len(), 1);
+
+            // The history dropped before is not available after extending the retention
+            rbac.set_history_retention(100).unwrap();
+            assert_eq!(rbac.check_access_at([1;32], [20;32], 1), Err(Error::HistoryNotKept));
+
+            // The retention is shared by the tenants, so their admins cannot change it
+            let accounts = default_accounts();
+            rbac.add_admin(accounts.bob).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(rbac.set_history_retention(0), Err(Error::NotOwner));
+            assert_eq!(rbac.history_retention(), 100);
         }
 
         #[ink::test]
-        fn remove_non_exist_permission_fail() {
+        fn clone_role_works() {
             let mut rbac = RBAC::default();
-
-            assert_eq!(
-                rbac.remove_role_from_permission([10;32], [20;32]),
-                Err(Error::RoleDoesNotExist)
-            );
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [21;32]).unwrap();
+            rbac.add_role_to_permission_on([10;32], [22;32], [30;32]).unwrap();
+            rbac.set_permission_condition([10;32], [21;32], Some(Condition {
+                any_of: vec![vec![Comparison { left: Operand::Context([1;32]), op: CmpOp::Eq, right: Operand::Const(1) }]],
+            })).unwrap();
+
+            rbac.clone_role([10;32], [11;32]).unwrap();
+            assert_eq!(rbac.read_permissions([11;32]), vec![[20;32], [21;32]]);
+            assert_eq!(rbac.read_permissions_on([11;32], [30;32]), vec![[22;32]]);
+            assert_eq!(rbac.read_permission_condition([11;32], [21;32]), rbac.read_permission_condition([10;32], [21;32]));
+
+            assert_eq!(rbac.clone_role([10;32], [11;32]), Err(Error::RoleExistsAlready));
+            assert_eq!(rbac.clone_role([12;32], [13;32]), Err(Error::RoleDoesNotExist));
         }
 
         #[ink::test]
-        fn remove_role_from_permission_works() {
+        fn role_template_works() {
             let mut rbac = RBAC::default();
-            let role_1 = [10;32];
-            let (perm_1, perm_2, perm_3) = ([20;32], [21;32], [22;32]);
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            rbac.add_role_to_permission(role_1, perm_2).unwrap();
-            rbac.add_role_to_permission(role_1, perm_3).unwrap();
+            rbac.set_role_template([40;32], vec![[20;32], [21;32], [20;32]]).unwrap();
+            assert_eq!(rbac.read_role_template([40;32]), vec![[20;32], [21;32]]);
 
-            // delete role from permission
-            assert_eq!(
-                rbac.remove_role_from_permission(role_1, perm_3),
-                Ok(())
-            );
+            rbac.instantiate_role_template([40;32], [10;32]).unwrap();
+            assert_eq!(rbac.read_permissions([10;32]), vec![[20;32], [21;32]]);
+            assert_eq!(rbac.instantiate_role_template([40;32], [10;32]), Err(Error::RoleExistsAlready));
 
-            // new permission count should be reduced 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 2)
+            // Nothing is added if a Permission needs approval
+            let accounts = default_accounts();
+            rbac.set_approval_policy(DEFAULT_TENANT, [21;32], vec![accounts.alice, accounts.bob], 2).unwrap();
+            assert_eq!(rbac.instantiate_role_template([40;32], [11;32]), Err(Error::ApprovalRequired));
+            assert!(rbac.read_permissions([11;32]).is_empty());
+
+            rbac.set_role_template([40;32], vec![]).unwrap();
+            assert_eq!(rbac.instantiate_role_template([40;32], [12;32]), Err(Error::TemplateDoesNotExist));
         }
 
-        //remove_remove_role_from_permission_wrong_role() when wrong role id passed
         #[ink::test]
-        fn remove_role_from_permission_wrong_role() {
-            let (perm_1, perm_2) = ([20;32], [21;32]);
-            let (role_1, role_not_exist) = ([10;32], [13;32]);
+        fn transfer_subject_works() {
             let mut rbac = RBAC::default();
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            rbac.add_role_to_permission(role_1, perm_2).unwrap();
- 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 2);
- 
-            // delete role from permission where role id is wrong
-            assert_eq!(
-                rbac.remove_role_from_permission(role_not_exist, perm_1),
-                Err(Error::RoleDoesNotExist)
-            );
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
+            rbac.add_user_or_group_to_role([2;32], [10;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
+            assert_eq!(rbac.transfer_subject([1;32], [2;32], None), Err(Error::UserGroupAreSame));
+
+            rbac.transfer_subject([1;32], [3;32], None).unwrap();
+            assert_eq!(rbac.read_user_group([2;32]), vec![[3;32]]);
+            assert_eq!(rbac.read_user_or_group_roles([3;32]), vec![[10;32], [11;32]]);
+            assert!(rbac.read_user_or_group_roles([1;32]).is_empty());
+            assert!(rbac.check_access([3;32], [21;32]));
+            assert!(!rbac.check_access([1;32], [21;32]));
+            assert_eq!(rbac.transfer_subject([1;32], [3;32], None), Err(Error::UserOrGroupDoesNotExist));
+
+            // The old User can take the place again
+            rbac.add_user_to_group([1;32], [2;32]).unwrap();
         }
- 
-        //remove_role_from_permission_wrong_permission() when wrong permission id passed
+
         #[ink::test]
-        fn remove_role_from_permission_wrong_permission() {
+        fn transfer_subject_with_grace_works() {
             let mut rbac = RBAC::default();
-            let role_1 = [10;32];
-            let (perm_1, perm_2, perm_not_exist) = ([20;32], [21;32], [22;32]);
-
-            rbac.add_role_to_permission(role_1, perm_1).unwrap();
-            rbac.add_role_to_permission(role_1, perm_2).unwrap();
- 
-            let vec_roles = rbac.read_permissions(role_1);
-            assert_eq!(vec_roles.len(), 2);
- 
-            // delete role from permission where permission id is wrong
-            assert_eq!(
-                rbac.remove_role_from_permission(role_1, perm_not_exist),
-                Err(Error::PermissionNotExistInRole)
-            );
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+
+            rbac.transfer_subject([1;32], [3;32], Some(1)).unwrap();
+            assert_eq!(rbac.read_transfer_grace([1;32]), Some(1));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(rbac.check_access([1;32], [20;32]));
+            assert!(rbac.check_access([3;32], [20;32]));
+            assert_eq!(rbac.end_transfer_grace([1;32]), Err(Error::TransferGraceNotOver));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(!rbac.check_access([1;32], [20;32]));
+            rbac.end_transfer_grace([1;32]).unwrap();
+            assert!(rbac.get_role(&[1;32]).is_empty());
+            assert_eq!(rbac.read_transfer_grace([1;32]), None);
+            assert_eq!(rbac.end_transfer_grace([1;32]), Err(Error::TransferGraceDoesNotExist));
         }
- 
+
         #[ink::test]
-        fn read_single_user_perm_empty() {
-            let rbac = RBAC::default();
+        fn new_link_keeps_transfer_grace() {
+            let mut rbac = RBAC::default();
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.add_role_to_permission([10;32], [20;32]).unwrap();
+            rbac.add_role_to_permission([11;32], [21;32]).unwrap();
 
-            assert_eq!(rbac.check_access([1;32], [11;32]), false);
-        }
+            // The role given within the grace period is kept, the transferred one is still removed
+            rbac.transfer_subject([1;32], [3;32], Some(1)).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [11;32]).unwrap();
+            assert_eq!(rbac.read_transfer_grace([1;32]), Some(1));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            assert!(!rbac.check_access([1;32], [20;32]));
+            assert!(rbac.check_access([1;32], [21;32]));
+            rbac.end_transfer_grace([1;32]).unwrap();
+            assert_eq!(rbac.get_role(&[1;32]), vec![[11;32]]);
+
+            // The role given after the grace period removes the transferred one first
+            rbac.transfer_subject([1;32], [4;32], Some(1)).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
+            assert_eq!(rbac.read_transfer_grace([1;32]), None);
+            assert_eq!(rbac.get_role(&[1;32]), vec![[12;32]]);
 
+            // The transferred role removed and given again within the grace period is a new grant
+            rbac.transfer_subject([1;32], [5;32], Some(1)).unwrap();
+            rbac.remove_user_or_group_from_role([1;32], [12;32]).unwrap();
+            rbac.add_user_or_group_to_role([1;32], [12;32]).unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>().unwrap();
+            rbac.end_transfer_grace([1;32]).unwrap();
+            assert_eq!(rbac.get_role(&[1;32]), vec![[12;32]]);
+
+            rbac.transfer_subject([1;32], [6;32], Some(BlockNumber::MAX)).unwrap();
+            assert_eq!(rbac.read_transfer_grace([1;32]), Some(BlockNumber::MAX));
+        }
 
         #[ink::test]
-        fn read_single_user_permission() {
+        fn transfer_subject_member_limit_fail() {
             let mut rbac = RBAC::default();
-            let user_did = [1;32];
-            let user_role_did = [10;32];
-            let user_permission_did = [11;32];
+            rbac.add_user_or_group_to_role([1;32], [10;32]).unwrap();
+            rbac.set_role_max_members([10;32], Some(1)).unwrap();
 
-            assert_eq!(rbac.check_access(user_did, user_permission_did), false);
-            rbac.add_user_or_group_to_role(user_did, user_role_did).unwrap();
-            rbac.add_role_to_permission(user_role_did, user_permission_did).unwrap();
-            assert_eq!(rbac.check_access(user_did, user_permission_did), true);
+            assert_eq!(rbac.transfer_subject([1;32], [3;32], Some(10)), Err(Error::MemberLimitReached));
+            assert!(rbac.get_role(&[3;32]).is_empty());
+            rbac.transfer_subject([1;32], [3;32], None).unwrap();
+            assert_eq!(rbac.get_role(&[3;32]), vec![[10;32]]);
         }
 
         #[ink::test]
-        fn read_user_in_group_permission() {
+        fn revoke_non_issued_token_fail() {
             let mut rbac = RBAC::default();
-            let user_did = [1;32];
-            let group_did = [2;32];
-            let group_role_did = [10;32];
-            let group_permission_did = [11;32];
-
-            rbac.add_user_to_group(user_did, group_did).unwrap();
-            rbac.add_user_or_group_to_role(group_did, group_role_did).unwrap();
-            rbac.add_role_to_permission(group_role_did, group_permission_did).unwrap();
-            assert_eq!(rbac.check_access(user_did, group_permission_did), true);
+            assert_eq!(
+                rbac.revoke_token(crate::token::build_token(DEFAULT_TENANT, [1;32], [11;32], 10, 0, 0)),
+                Err(Error::TokenDoesNotExist)
+            );
         }
     }
 }
@@ -0,0 +1,90 @@
+//! Off-chain helpers for the RBAC capability tokens.
+//!
+//! A device syncs the hashes of the tokens issued in its tenant from `read_issued_tokens`
+//! and the revocation list from `read_revoked_tokens`, and checks the tokens it is shown locally
+//! instead of asking the contract for every access decision.
+
+pub use crate::rbac::{
+    CapabilityToken,
+    TenantId,
+    TokenHash,
+};
+
+type DIDType = [u8; 32];
+pub type BlockNumber = <ink_env::DefaultEnvironment as ink_env::Environment>::BlockNumber;
+
+// Build the token the same way as the contract does in `issue_token`
+pub fn build_token(
+    tenant: TenantId,
+    user_did: DIDType,
+    permission_did: DIDType,
+    expiry: BlockNumber,
+    nonce: u64,
+    policy_version: u64,
+) -> CapabilityToken {
+    CapabilityToken {
+        tenant,
+        user_did,
+        permission_did,
+        expiry,
+        nonce,
+        policy_version,
+    }
+}
+
+// Verify the token is in the issued tokens of its tenant recorded on-chain,
+// has not expired at the block number and is not in the revocation list of its tenant
+pub fn verify_token(
+    token: &CapabilityToken,
+    issued_tokens: &[TokenHash],
+    block_number: BlockNumber,
+    revoked_tokens: &[TokenHash],
+) -> bool {
+    let hash = token.hash();
+    issued_tokens.contains(&hash) &&
+        token.expiry >= block_number &&
+        !revoked_tokens.contains(&hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hashes of the tokens issued on-chain, as read_issued_tokens returns them
+    fn issued_tokens() -> Vec<TokenHash> {
+        vec![
+            build_token([0;32], [1;32], [11;32], 10, 0, 3).hash(),
+            build_token([0;32], [2;32], [11;32], 10, 1, 3).hash(),
+        ]
+    }
+
+    #[test]
+    fn verify_token_works() {
+        let token = build_token([0;32], [1;32], [11;32], 10, 0, 3);
+        assert!(verify_token(&token, &issued_tokens(), 10, &[]));
+    }
+
+    #[test]
+    fn verify_token_not_issued_fail() {
+        let token = build_token([0;32], [3;32], [11;32], 10, 2, 3);
+        assert!(!verify_token(&token, &issued_tokens(), 10, &[]));
+        assert!(!verify_token(&token, &[], 10, &[]));
+    }
+
+    #[test]
+    fn verify_token_modified_fail() {
+        let forged = build_token([0;32], [1;32], [11;32], 20, 0, 3);
+        assert!(!verify_token(&forged, &issued_tokens(), 10, &[]));
+        // The tenant is part of the hash
+        let other_tenant = build_token([7;32], [1;32], [11;32], 10, 0, 3);
+        assert!(!verify_token(&other_tenant, &issued_tokens(), 10, &[]));
+    }
+
+    #[test]
+    fn verify_token_expired_or_revoked_fail() {
+        let token = build_token([0;32], [1;32], [11;32], 10, 0, 3);
+        let hash = token.hash();
+        assert!(!verify_token(&token, &issued_tokens(), 11, &[]));
+        assert!(!verify_token(&token, &issued_tokens(), 5, &[hash]));
+    }
+}